@@ -1,5 +1,11 @@
 use clap::{Parser, Subcommand, ArgGroup, ValueEnum};
-use crate::case::Case;
+use crate::case::{Case, SourceCase};
+use crate::syntax::{Language, IdentifierKind};
+use crate::structured::{Format, PathPattern};
+use crate::report::{KeyGrouping, TieBreak};
+use crate::table::TableFormat;
+use crate::rename::RenameTarget;
+use crate::stats::ConfidenceLevel;
 
 #[derive(Parser)]
 #[command(author, version, about)]
@@ -20,6 +26,14 @@ pub enum Command {
         #[arg(short, long, group = "input_group", help = "Detect cases from stdin")]
         stdin: bool,
 
+        #[arg(
+            long,
+            group = "input_group",
+            conflicts_with_all = ["language", "kind", "format", "table", "shell"],
+            help = "Detect cases of the names of all files and directories under this path, leaving out extensions and listing the outliers",
+        )]
+        filenames: Option<String>,
+
         #[arg(
             short,
             long,
@@ -34,6 +48,60 @@ pub enum Command {
 
         #[arg(short, long, group = "output_group", help = "Print all used cases")]
         report: Option<Option<ReportType>>,
+
+        #[arg(long, help = "Parse the input as source code of this language, ignoring keywords, comments and literals")]
+        language: Option<Language>,
+
+        #[arg(long, help = "Only detect cases of identifiers of this kind (infers the language from the file extension if not given)")]
+        kind: Vec<IdentifierKind>,
+
+        #[arg(long, conflicts_with = "language", help = "Parse the input as a structured document of this format and only detect the cases of the keys of its objects (infers the format from the file extension if not given)")]
+        format: Option<Format>,
+
+        #[arg(long, help = "Only detect cases of keys at most this many objects deep (infers the format from the file extension if not given)")]
+        max_depth: Option<usize>,
+
+        #[arg(long, help = "Only detect cases of keys whose path matches this pattern, such as '$.data.*' (infers the format from the file extension if not given)")]
+        path: Vec<PathPattern>,
+
+        #[arg(long, help = "Also detect the cases of keys separately for each group (infers the format from the file extension if not given)")]
+        group_by: Option<KeyGrouping>,
+
+        #[arg(long, conflicts_with_all = ["language", "format"], help = "Parse the input as a table in this format and only detect the cases of its header")]
+        table: Option<TableFormat>,
+
+        #[arg(long, help = "Separate the cells of the table with this character instead of the format's default (infers the table format from the file extension if not given)")]
+        delimiter: Option<char>,
+
+        #[arg(long, help = "Detect the cases of the values of this column instead of the header, given by its name or 1-based number (infers the table format from the file extension if not given)")]
+        column: Vec<String>,
+
+        #[arg(long, conflicts_with_all = ["language", "format", "table"], help = "Parse the input as a shell script or .env file and only detect the cases of variable names")]
+        shell: bool,
+
+        #[arg(long, help = "List the tokens that don't use this case (or the most frequent one if not given) along with their locations")]
+        non_conforming: Option<Option<Case>>,
+
+        #[arg(long, value_enum, default_value_t = TieBreak::Fail, help = "How to pick the most frequent case when several cases are used the most")]
+        tie_break: TieBreak,
+
+        #[arg(long, ignore_case = true, help = "Case to pick on a tie with --tie-break preferred, in order of preference")]
+        prefer: Vec<Case>,
+
+        #[arg(long, value_parser = parse_share, help = "Report the input as mixed unless the most frequent case is used by at least this share of the tokens, between 0 and 1")]
+        min_share: Option<f32>,
+
+        #[arg(long, help = "Report the input as mixed unless it has at least this many tokens")]
+        min_tokens: Option<u32>,
+
+        #[arg(long, value_enum, help = "Also show a confidence interval (in percent) for the share of each case, and test whether the most frequent case is used more than the next one not just by chance")]
+        confidence: Option<ConfidenceLevel>,
+
+        #[arg(long, help = "Print the result as JSON, including all the cases tied for the most frequent one")]
+        json: bool,
+
+        #[arg(long, requires = "non_conforming", conflicts_with_all = ["json", "filenames"], help = "Print the non-conforming tokens as a SARIF log for code scanning tools")]
+        sarif: bool,
     },
     #[command(about = "Convert between case types")]
     Convert {
@@ -51,18 +119,221 @@ pub enum Command {
         )]
         inline: Option<String>,
 
-        #[arg(long, required=true)]
-        from: Vec<Case>,
+        #[arg(long, ignore_case = true, help = "Case to convert from, once for each --to or several times for a single --to (converts from any case if not given)")]
+        from: Vec<SourceCase>,
 
-        #[arg(long, required=true)]
+        #[arg(long, required_unless_present = "to_main", ignore_case = true, help = "Case to convert to")]
         to: Vec<Case>,
 
+        #[arg(long, conflicts_with_all = ["from", "to"], help = "Convert every token to the case most used in the input")]
+        to_main: bool,
+
+        #[arg(long, value_enum, requires = "to_main", conflicts_with_all = ["from", "to"], help = "How --to-main picks the case when several cases are used the most (fail if not given)")]
+        tie_break: Option<TieBreak>,
+
+        #[arg(long, ignore_case = true, requires = "to_main", conflicts_with_all = ["from", "to"], help = "Case to pick on a tie with --tie-break preferred, in order of preference")]
+        prefer: Vec<Case>,
+
         #[arg(long, group = "output_group", help = "Print to stdout")]
         stdout: bool,
 
         #[arg(short, long, group = "output_group", help = "Print to file")]
         output: Option<String>,
 
+        #[arg(long, help = "Parse the input as source code of this language, ignoring keywords, comments and literals")]
+        language: Option<Language>,
+
+        #[arg(long, help = "Only convert identifiers of this kind (infers the language from the file extension if not given)")]
+        kind: Vec<IdentifierKind>,
+
+        #[arg(long, conflicts_with = "language", help = "Parse the input as a structured document of this format and only convert the keys of its objects (infers the format from the file extension if not given)")]
+        format: Option<Format>,
+
+        #[arg(long, help = "Only convert keys at most this many objects deep (infers the format from the file extension if not given)")]
+        max_depth: Option<usize>,
+
+        #[arg(long, help = "Only convert keys whose path matches this pattern, such as '$.data.*', '$.items[*].id' or '$..id' (infers the format from the file extension if not given)")]
+        path: Vec<PathPattern>,
+
+        #[arg(long, conflicts_with_all = ["language", "format"], help = "Parse the input as a table in this format and only convert its header")]
+        table: Option<TableFormat>,
+
+        #[arg(long, help = "Separate the cells of the table with this character instead of the format's default (infers the table format from the file extension if not given)")]
+        delimiter: Option<char>,
+
+        #[arg(long, help = "Convert the values of this column instead of the header, given by its name or 1-based number (infers the table format from the file extension if not given)")]
+        column: Vec<String>,
+
+        #[arg(long, conflicts_with_all = ["language", "format", "table"], help = "Parse the input as a shell script or .env file and only convert variable names, including their references")]
+        shell: bool,
+
+        #[arg(long, value_enum, default_value_t = CollisionHandling::Warn, help = "What to do when distinct tokens would be converted to the same one")]
+        on_collision: CollisionHandling,
+
+        #[arg(long, help = "Don't convert anything if some token can't be converted back to its original case")]
+        verify_roundtrip: bool,
+
+        #[arg(long, help = "Split tokens without word boundaries (such as getusername) using a bundled English dictionary")]
+        dictionary: bool,
+
+        #[arg(long, help = "File with additional words for the dictionary, one per line (implies --dictionary)")]
+        vocabulary: Vec<String>,
+
+        #[arg(long, help = "Ask whether to convert each token, reading the answers from stdin")]
+        interactive: bool,
+    },
+    #[command(about = "Consistently convert identifiers across several files, in place")]
+    Refactor {
+        #[arg(required = true, help = "Files to convert")]
+        files: Vec<String>,
+
+        #[arg(long, ignore_case = true, help = "Case to convert from, once for each --to or several times for a single --to (converts from any case if not given)")]
+        from: Vec<SourceCase>,
+
+        #[arg(long, required_unless_present = "to_main", ignore_case = true, help = "Case to convert to")]
+        to: Vec<Case>,
+
+        #[arg(long, conflicts_with_all = ["from", "to"], help = "Convert every identifier to the case most used in the files")]
+        to_main: bool,
+
+        #[arg(long, value_enum, requires = "to_main", conflicts_with_all = ["from", "to"], help = "Whether --to-main looks for the most used case in all files together or in each file (global if not given)")]
+        scope: Option<MainCaseScope>,
+
+        #[arg(long, value_enum, requires = "to_main", conflicts_with_all = ["from", "to"], help = "How --to-main picks the case when several cases are used the most (fail if not given)")]
+        tie_break: Option<TieBreak>,
+
+        #[arg(long, ignore_case = true, requires = "to_main", conflicts_with_all = ["from", "to"], help = "Case to pick on a tie with --tie-break preferred, in order of preference")]
+        prefer: Vec<Case>,
+
+        #[arg(long, help = "Parse the files as source code of this language, ignoring keywords, comments and literals")]
+        language: Option<Language>,
+
+        #[arg(long, help = "Only convert identifiers of this kind (infers the language from each file extension if not given)")]
+        kind: Vec<IdentifierKind>,
+
+        #[arg(long, help = "Only print the renames that would be made")]
+        dry_run: bool,
+
+        #[arg(long, value_enum, default_value_t = CollisionHandling::Abort, help = "What to do when distinct identifiers would be renamed to the same one")]
+        on_collision: CollisionHandling,
+
+        #[arg(long, help = "Split tokens without word boundaries (such as getusername) using a bundled English dictionary")]
+        dictionary: bool,
+
+        #[arg(long, help = "File with additional words for the dictionary, one per line (implies --dictionary)")]
+        vocabulary: Vec<String>,
+    },
+    #[command(about = "Rename files and directories, keeping their extensions")]
+    Rename {
+        #[arg(required = true, help = "Files and directories to rename")]
+        paths: Vec<String>,
+
+        #[arg(long, ignore_case = true, help = "Case to convert from, once for each --to or several times for a single --to (converts from any case if not given)")]
+        from: Vec<SourceCase>,
+
+        #[arg(long, required = true, ignore_case = true, help = "Case to convert to")]
+        to: Vec<Case>,
+
+        #[arg(short, long, help = "Also rename everything inside the given directories")]
+        recursive: bool,
+
+        #[arg(long, value_enum, default_value_t = RenameTarget::Files, help = "What kind of entries to rename")]
+        target: RenameTarget,
+
+        #[arg(long, help = "Only print the renames that would be made")]
+        dry_run: bool,
+
+        #[arg(long, help = "Rename through git mv so the renames are staged")]
+        git: bool,
+
+        #[arg(long, help = "Split names without word boundaries (such as getusername) using a bundled English dictionary")]
+        dictionary: bool,
+
+        #[arg(long, help = "File with additional words for the dictionary, one per line (implies --dictionary)")]
+        vocabulary: Vec<String>,
+    },
+    #[command(about = "Report the tokens that don't use the expected case, failing if there are any")]
+    #[command(group = ArgGroup::new("changes").multiple(false))]
+    Lint {
+        #[arg(required_unless_present_any = ["diff", "base"], help = "Files to check (with --diff or --base, only their added lines, or the added lines of all changed files if no files are given)")]
+        files: Vec<String>,
+
+        #[arg(long, ignore_case = true, help = "Case the tokens have to use (the most frequent one in the files if not given)")]
+        case: Option<Case>,
+
+        #[arg(long, value_enum, conflicts_with = "case", help = "How to pick the expected case when several cases are used the most in the files (fail if not given)")]
+        tie_break: Option<TieBreak>,
+
+        #[arg(long, ignore_case = true, conflicts_with = "case", help = "Case to pick on a tie with --tie-break preferred, in order of preference")]
+        prefer: Vec<Case>,
+
+        #[arg(long, help = "Parse the files as source code of this language, ignoring keywords, comments and literals (infers the language from each file extension if not given)")]
+        language: Option<Language>,
+
+        #[arg(long, help = "Only check identifiers of this kind")]
+        kind: Vec<IdentifierKind>,
+
+        #[arg(long, group = "changes", help = "Only check the lines added by this unified diff, such as the output of git diff ('-' reads it from stdin)")]
+        diff: Option<String>,
+
+        #[arg(long, group = "changes", help = "Only check the lines added since this git commit, branch or tag, including uncommitted changes")]
+        base: Option<String>,
+
+        #[arg(long, help = "Ignore the violations recorded in this baseline file, and report its entries that are now fixed")]
+        baseline: Option<String>,
+
+        #[arg(long, conflicts_with_all = ["diff", "base", "baseline"], help = "Record the current violations in this baseline file instead of reporting them")]
+        write_baseline: Option<String>,
+
+        #[arg(long, conflicts_with = "write_baseline", help = "Print the violations as a SARIF log for code scanning tools")]
+        sarif: bool,
+    },
+    #[command(about = "Run a language server over stdio reporting the tokens that don't use the expected case")]
+    Lsp {
+        #[arg(long, ignore_case = true, help = "Case the tokens have to use (the most frequent one in each document if not given)")]
+        case: Option<Case>,
+
+        #[arg(long, value_enum, conflicts_with = "case", help = "How to pick the expected case when several cases are used the most in a document (fail if not given)")]
+        tie_break: Option<TieBreak>,
+
+        #[arg(long, ignore_case = true, conflicts_with = "case", help = "Case to pick on a tie with --tie-break preferred, in order of preference")]
+        prefer: Vec<Case>,
+
+        #[arg(long, help = "Parse every document as source code of this language (infers the language from each document extension if not given, and doesn't check documents without one)")]
+        language: Option<Language>,
+
+        #[arg(long, help = "Only check identifiers of this kind")]
+        kind: Vec<IdentifierKind>,
+    },
+    #[command(about = "Show how the cases used in a git repository changed over its history")]
+    History {
+        #[arg(default_value = ".", help = "Path of the repository")]
+        repository: String,
+
+        #[arg(long, default_value = "HEAD", help = "Follow the history of this commit, branch or tag, through first parents only")]
+        revision: String,
+
+        #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..), help = "Only look at every this many commits, always including the latest one")]
+        every: u32,
+
+        #[arg(long, help = "Only read source files of this language (otherwise, all files whose language is known from their extension are read)")]
+        language: Option<Language>,
+
+        #[arg(long, help = "Only count identifiers of this kind")]
+        kind: Vec<IdentifierKind>,
+
+        #[arg(long, help = "Only read files under this path of the repository")]
+        path: Vec<String>,
+
+        #[arg(short, long, value_enum, default_value_t = ReportType::Percentage, help = "How to show the use of each case")]
+        report: ReportType,
+
+        #[arg(long, help = "Print the result as JSON instead of CSV")]
+        json: bool,
+    },
+    #[command(about = "Explain how the case of a token is detected and converted")]
+    Explain {
+        token: String,
     },
     #[command(about = "Generate completion scripts for a given shell")]
     Completions {
@@ -80,3 +351,32 @@ pub enum ReportType {
     #[value(help = "Percentage of times each case is used in input")]
     Percentage,
 }
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
+pub enum CollisionHandling {
+    #[value(help = "Don't look for collisions, which lets the input be converted as it's read")]
+    Ignore,
+    #[value(help = "Report collisions but convert anyway")]
+    Warn,
+    #[value(help = "Report collisions and don't convert anything")]
+    Abort,
+    #[value(help = "Add a numeric suffix to colliding tokens so they stay distinct")]
+    Disambiguate,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
+pub enum MainCaseScope {
+    #[value(help = "Convert all files to the case most used in all of them")]
+    Global,
+    #[value(help = "Convert each file to the case most used in it")]
+    File,
+}
+
+fn parse_share(value: &str) -> Result<f32, String> {
+    let share: f32 = value.parse().map_err(|_| format!("'{value}' isn't a number"))?;
+    if (0.0..=1.0).contains(&share) {
+        Ok(share)
+    } else {
+        Err(format!("'{value}' isn't between 0 and 1"))
+    }
+}
@@ -0,0 +1,208 @@
+use crate::case::Case;
+use crate::diff;
+use crate::lint::Violation;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::Path;
+use strum::IntoEnumIterator;
+use std::fmt::{Formatter, Display, Result as FormatResult};
+use thiserror::Error as ThisError;
+
+#[allow(clippy::enum_variant_names)]
+#[derive(ThisError, Debug)]
+pub enum BaselineError {
+    #[error("the baseline isn't valid JSON: {0}")]
+    JsonError(#[from] serde_json::Error),
+    #[error("the baseline needs a list of violations")]
+    MissingViolationsError,
+    #[error("entry {0} of the baseline needs a file, a token and a fingerprint")]
+    InvalidEntryError(usize),
+    #[error("'{0}' isn't a case the baseline can expect")]
+    InvalidCaseError(String),
+}
+
+/// A violation accepted by a baseline. Instead of its line number, which changes
+/// whenever lines are added above it, it's identified by a fingerprint of its line
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct BaselineEntry {
+    pub file: String,
+    pub token: String,
+    pub fingerprint: String,
+}
+
+impl BaselineEntry {
+    pub fn of(violation: &Violation) -> BaselineEntry {
+        BaselineEntry {
+            file: normalize(&violation.location.origin),
+            token: violation.token.clone(),
+            fingerprint: fingerprint(&violation.context),
+        }
+    }
+}
+
+impl Display for BaselineEntry {
+    fn fmt(&self, f: &mut Formatter) -> FormatResult {
+        write!(f, "{}: '{}'", self.file, self.token)
+    }
+}
+
+/// Violations that already existed when the baseline was written, along with the
+/// case that was expected then, so later runs keep expecting it
+#[derive(Debug, PartialEq, Eq, Default)]
+pub struct Baseline {
+    pub case: Option<Case>,
+    pub entries: Vec<BaselineEntry>,
+}
+
+impl Baseline {
+    pub fn from_violations(case: Case, violations: &[Violation]) -> Baseline {
+        Baseline { case: Some(case), entries: violations.iter().map(BaselineEntry::of).collect() }
+    }
+
+    pub fn parse(text: &str) -> Result<Baseline, BaselineError> {
+        let value: Value = serde_json::from_str(text)?;
+        let entries = value["violations"].as_array().ok_or(BaselineError::MissingViolationsError)?;
+
+        let entries = entries.iter()
+            .enumerate()
+            .map(|(index, entry)| {
+                let field = |name: &str| entry[name].as_str().map(String::from).ok_or(BaselineError::InvalidEntryError(index + 1));
+                Ok(BaselineEntry { file: normalize(&field("file")?), token: field("token")?, fingerprint: field("fingerprint")? })
+            })
+            .collect::<Result<Vec<_>, BaselineError>>()?;
+
+        // Baselines written before the case was recorded don't have it
+        let case = match value["case"].as_str() {
+            Some(name) => Some(Case::iter().find(|case| case.to_string() == name).ok_or_else(|| BaselineError::InvalidCaseError(String::from(name)))?),
+            None => None,
+        };
+        Ok(Baseline { case, entries })
+    }
+
+    pub fn to_json(&self) -> Result<String, BaselineError> {
+        let entries: Vec<Value> = self.entries.iter()
+            .map(|entry| json!({ "file": entry.file, "token": entry.token, "fingerprint": entry.fingerprint }))
+            .collect();
+        Ok(serde_json::to_string_pretty(&json!({ "case": self.case.map(|it| it.to_string()), "violations": entries }))?)
+    }
+
+    /// Splits `violations` into those that aren't in the baseline, and the baseline
+    /// entries of the `checked` files that no violation matched anymore. An entry
+    /// only suppresses one violation, so copies of a line still get reported
+    pub fn apply(&self, violations: Vec<Violation>, checked: &[String]) -> (Vec<Violation>, Vec<BaselineEntry>) {
+        let mut remaining: HashMap<&BaselineEntry, usize> = HashMap::new();
+        for entry in &self.entries {
+            *remaining.entry(entry).or_default() += 1;
+        }
+
+        let new = violations.into_iter()
+            .filter(|violation| match remaining.get_mut(&BaselineEntry::of(violation)) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    false
+                },
+                _ => true,
+            })
+            .collect();
+
+        let checked: Vec<String> = checked.iter().map(|it| normalize(it)).collect();
+        let mut fixed = Vec::new();
+        for entry in &self.entries {
+            if !checked.contains(&entry.file) {
+                continue;
+            }
+            if let Some(count) = remaining.get_mut(entry) {
+                if *count > 0 {
+                    *count -= 1;
+                    fixed.push(entry.clone());
+                }
+            }
+        }
+        (new, fixed)
+    }
+}
+
+/// The path of a file as stored in a baseline, so it matches however the file is named
+fn normalize(file: &str) -> String {
+    diff::normalize(Path::new(file)).display().to_string()
+}
+
+/// 64-bit FNV-1a hash of a line, as hex. Unlike the hasher of the standard
+/// library, it's guaranteed to stay the same across versions
+fn fingerprint(context: &str) -> String {
+    let hash = context.bytes().fold(0xcbf29ce484222325_u64, |hash, byte| (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3));
+    format!("{hash:016x}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::case::Case;
+    use crate::lint;
+    use crate::scan::Scanner;
+    use crate::syntax::Language;
+    use indoc::indoc;
+    use std::error::Error;
+
+    #[test]
+    fn suppresses_violations_of_baseline() -> Result<(), Box<dyn Error>> {
+        // ARRANGE
+        let scanner = Scanner::Syntax(Language::Python, vec![]);
+        let before = indoc! {"
+            def getUser(user_id):
+                userName = user_id
+                return userName
+        "};
+        let after = indoc! {"
+            import os
+
+            def get_user(user_id):
+                userName = user_id
+                print(otherName)
+                return userName
+        "};
+        let baseline = Baseline::parse(&Baseline::from_violations(Case::SnakeCase, &lint::violations("./a.py", before, &scanner, Case::SnakeCase, None)?).to_json()?)?;
+
+        // ACT
+        let (new, fixed) = baseline.apply(lint::violations("a.py", after, &scanner, Case::SnakeCase, None)?, &[String::from("a.py")]);
+
+        // ASSERT
+        assert_eq!(baseline.case, Some(Case::SnakeCase));
+        assert_eq!(new.iter().map(|it| it.to_string()).collect::<Vec<_>>(), vec!["a.py:5:11: 'otherName' isn't snake_case"]);
+        assert_eq!(fixed.iter().map(|it| it.to_string()).collect::<Vec<_>>(), vec!["a.py: 'getUser'"]);
+        Ok(())
+    }
+
+    #[test]
+    fn only_reports_fixed_entries_of_checked_files() {
+        let entry = BaselineEntry { file: String::from("b.py"), token: String::from("fooBar"), fingerprint: fingerprint("fooBar = 1") };
+        let baseline = Baseline { case: None, entries: vec![entry] };
+
+        let (new, fixed) = baseline.apply(vec![], &[String::from("a.py")]);
+
+        assert!(new.is_empty());
+        assert!(fixed.is_empty());
+    }
+
+    #[test]
+    fn invalid_entry() {
+        let result = Baseline::parse(r#"{ "violations": [{ "file": "a.py", "token": "fooBar" }] }"#);
+
+        assert!(matches!(result, Err(BaselineError::InvalidEntryError(1))));
+    }
+
+    #[test]
+    fn missing_violations() {
+        assert!(matches!(Baseline::parse(r#"{ "case": "snake_case" }"#), Err(BaselineError::MissingViolationsError)));
+        assert!(matches!(Baseline::parse(r#"{ "violations": {} }"#), Err(BaselineError::MissingViolationsError)));
+    }
+
+    #[test]
+    fn normalizes_paths_of_entries() -> Result<(), BaselineError> {
+        let baseline = Baseline::parse(r#"{ "case": "snake_case", "violations": [{ "file": "./src/../src/a.py", "token": "fooBar", "fingerprint": "0" }] }"#)?;
+
+        assert_eq!(baseline.entries[0].file, "src/a.py");
+        assert!(matches!(Baseline::parse(r#"{ "case": "Title Case", "violations": [] }"#), Err(BaselineError::InvalidCaseError(_))));
+        Ok(())
+    }
+}
@@ -1,7 +1,10 @@
 use strum_macros::EnumIter;
 use std::fmt::{Formatter, Display, Result as FormatResult};
 use clap::ValueEnum;
+use clap::builder::PossibleValue;
+use strum::IntoEnumIterator;
 
+#[allow(clippy::enum_variant_names)]
 #[derive(Debug, EnumIter, PartialEq, Eq, Hash, Clone, Copy, ValueEnum, Ord, PartialOrd)]
 pub enum Case {
     // Uppercase
@@ -31,6 +34,54 @@ impl Display for Case {
     }
 }
 
+/// A case to convert from, which may be any case at all
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SourceCase {
+    Any,
+    Case(Case),
+}
+
+impl ValueEnum for SourceCase {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[
+            SourceCase::Any,
+            SourceCase::Case(Case::PascalCase),
+            SourceCase::Case(Case::ShoutingSnakeCase),
+            SourceCase::Case(Case::CamelCase),
+            SourceCase::Case(Case::SnakeCase),
+            SourceCase::Case(Case::KebabCase),
+        ]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        match self {
+            SourceCase::Any => Some(PossibleValue::new("any").alias("auto").help("or auto, any case other than the target one")),
+            SourceCase::Case(case) => case.to_possible_value(),
+        }
+    }
+}
+
+/// Pairs the cases to convert from with the cases to convert to. Either there's
+/// a source case for each target case, or all source cases go to a single target
+/// case. No source cases at all means converting from any case. Returns `None` if
+/// the numbers of cases don't fit together
+pub fn from_to_cases(from: &[SourceCase], to: &[Case]) -> Option<Vec<(Case, Case)>> {
+    let from = if from.is_empty() { &[SourceCase::Any][..] } else { from };
+    let pairs: Vec<(SourceCase, Case)> = match to {
+        [target] => from.iter().map(|source| (*source, *target)).collect(),
+        _ if from.len() == to.len() => from.iter().copied().zip(to.iter().copied()).collect(),
+        _ => return None,
+    };
+
+    Some(pairs.into_iter()
+              .flat_map(|(source, target)| match source {
+                  // A token already in the target case is left as it is
+                  SourceCase::Any => Case::iter().filter(|case| *case != target).map(|case| (case, target)).collect(),
+                  SourceCase::Case(case) => vec![(case, target)],
+              })
+              .collect())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -39,4 +90,33 @@ mod tests {
     fn display_camel_case() {
         assert_eq!(format!("{}", Case::CamelCase), "camelCase");
     }
+
+    #[test]
+    fn pairs_source_and_target_cases() {
+        let from = [SourceCase::Case(Case::CamelCase), SourceCase::Case(Case::KebabCase)];
+        let to = [Case::SnakeCase, Case::PascalCase];
+
+        assert_eq!(from_to_cases(&from, &to), Some(vec![(Case::CamelCase, Case::SnakeCase), (Case::KebabCase, Case::PascalCase)]));
+        assert_eq!(from_to_cases(&from, &to[..1]), Some(vec![(Case::CamelCase, Case::SnakeCase), (Case::KebabCase, Case::SnakeCase)]));
+        assert_eq!(from_to_cases(&from[..1], &to), None);
+    }
+
+    #[test]
+    fn converts_any_other_case_without_source_cases() {
+        let expected = Some(vec![
+            (Case::PascalCase, Case::SnakeCase),
+            (Case::ShoutingSnakeCase, Case::SnakeCase),
+            (Case::CamelCase, Case::SnakeCase),
+            (Case::KebabCase, Case::SnakeCase),
+        ]);
+
+        assert_eq!(from_to_cases(&[], &[Case::SnakeCase]), expected);
+        assert_eq!(from_to_cases(&[SourceCase::Any], &[Case::SnakeCase]), expected);
+    }
+
+    #[test]
+    fn parses_source_cases_ignoring_case() {
+        assert_eq!(SourceCase::from_str("auto", false), Ok(SourceCase::Any));
+        assert_eq!(SourceCase::from_str("Camel-Case", true), Ok(SourceCase::Case(Case::CamelCase)));
+    }
 }
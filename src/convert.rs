@@ -1,8 +1,13 @@
 use crate::{case::Case, detect::CaseDetect};
+use crate::syntax::{self, Language, IdentifierKind};
 use std::io::{Write, BufRead};
-use heck::*;
+use crate::words::{split_words, join_words};
+use crate::dictionary::Dictionary;
+use crate::matchers::CaseMatcher;
+use strum::IntoEnumIterator;
 use split_preserve::*;
 use std::error::Error;
+use std::fmt::{Formatter, Display, Result as FormatResult};
 
 pub trait ConvertCaseTo {
     fn convert_case_to(&self, target_case: Case) -> String;
@@ -10,53 +15,116 @@ pub trait ConvertCaseTo {
 
 impl ConvertCaseTo for str {
     fn convert_case_to(&self, target_case: Case) -> String {
-        match target_case {
-            Case::PascalCase => self.to_upper_camel_case(),
-            Case::ShoutingSnakeCase => self.to_shouty_snake_case(),
-            Case::CamelCase => self.to_lower_camel_case(),
-            Case::SnakeCase => self.to_snake_case(),
-            Case::KebabCase => self.to_kebab_case(),
-        }
+        join_words(&split_words(self), target_case)
+    }
+}
+
+/// Converts `token` according to the first conversion whose source case
+/// matches the detected case of the token. Returns `None` if the token
+/// is left untouched.
+/// If a dictionary is given, it's used to split the words of the token. Tokens
+/// without word boundaries that match several cases (such as `getusername`) are
+/// then converted according to the first conversion from any of those cases.
+pub fn convert_token(token: &str, from_to_cases: &[(Case, Case)], dictionary: Option<&Dictionary>) -> Option<String> {
+    let words = match dictionary {
+        Some(dictionary) => dictionary.split_words(token),
+        None => split_words(token),
+    };
+
+    let source_cases = match Case::detect(token).ok()? {
+        Some(case) => vec![case],
+        None if words.len() > 1 => Case::iter().filter(|case| case.matcher().is_match(token)).collect(),
+        None => return None,
+    };
+
+    from_to_cases.iter()
+                 .find(|(source_case, _)| source_cases.contains(source_case))
+                 .map(|(_, target_case)| join_words(&words, *target_case))
+}
+
+/// A conversion that can't be undone by converting back to the original case
+#[derive(Debug, PartialEq, Eq)]
+pub struct LossyConversion {
+    pub original: String,
+    pub converted: String,
+    pub roundtrip: String,
+}
+
+impl Display for LossyConversion {
+    fn fmt(&self, f: &mut Formatter) -> FormatResult {
+        write!(f, "{} -> {} -> {}", self.original, self.converted, self.roundtrip)
     }
 }
 
+/// Converts `token` according to `from_to_cases` and then back to its detected case,
+/// returning the conversion if that doesn't reproduce the original token
+pub fn check_roundtrip(token: &str, from_to_cases: &[(Case, Case)], dictionary: Option<&Dictionary>) -> Option<LossyConversion> {
+    let original_case = Case::detect(token).ok()??;
+    let converted = convert_token(token, from_to_cases, dictionary)?;
+    let roundtrip = converted.convert_case_to(original_case);
+
+    (roundtrip != token).then(|| LossyConversion {
+        original: String::from(token),
+        converted,
+        roundtrip,
+    })
+}
+
 pub trait BufferedConvert {
-    fn buffered_convert(&mut self, from_to_cases: &Vec<(Case, Case)>, output: Box<&mut dyn Write>) -> Result<(), Box<dyn Error>>;
+    fn buffered_convert(&mut self, from_to_cases: &[(Case, Case)], dictionary: Option<&Dictionary>, output: &mut dyn Write) -> Result<(), Box<dyn Error>>;
 }
 
 impl <T: BufRead> BufferedConvert for T {
-    fn buffered_convert(&mut self, from_to_cases: &Vec<(Case, Case)>, output: Box<&mut dyn Write>) -> Result<(), Box<dyn Error>> {
-        let mut lines = self.lines().peekable();
-        while let Some(line) = lines.next() {
-            let line = line?;
+    fn buffered_convert(&mut self, from_to_cases: &[(Case, Case)], dictionary: Option<&Dictionary>, output: &mut dyn Write) -> Result<(), Box<dyn Error>> {
+        // Lines keep their line endings, so the output only differs from the input in its tokens
+        let mut line = String::new();
+        while self.read_line(&mut line)? > 0 {
             let converted_line = SplitPreserveWS::new(line.as_str())
-                .map_words(|w| {
-                    let case = Case::detect(w).unwrap();
-                    if let Some(case) = case {
-                        for (source_case, target_case) in from_to_cases {
-                           if *source_case == case {
-                                return w.convert_case_to(*target_case);
-                           } 
-                        }
-                    } 
-                    return String::from(w);
-                })
+                .map_words(|w| convert_token(w, from_to_cases, dictionary).unwrap_or_else(|| String::from(w)))
                 .collect::<String>()
             ;
-            (*output).write_all(converted_line.as_bytes())?;
-            if lines.peek().is_some() {
-                (*output).write_all(b"\n")?;
-            }
+            output.write_all(converted_line.as_bytes())?;
+            line.clear();
         }
 
         Ok(())
     }
 }
 
+pub trait SyntaxConvert {
+    fn syntax_convert(&mut self, language: Language, kinds: &[IdentifierKind], from_to_cases: &[(Case, Case)], dictionary: Option<&Dictionary>, output: &mut dyn Write) -> Result<(), Box<dyn Error>>;
+}
+
+impl <T: BufRead> SyntaxConvert for T {
+    /// Converts only the identifiers of the given kinds (or all of them if `kinds`
+    /// is empty), leaving keywords, comments and literals untouched
+    fn syntax_convert(&mut self, language: Language, kinds: &[IdentifierKind], from_to_cases: &[(Case, Case)], dictionary: Option<&Dictionary>, output: &mut dyn Write) -> Result<(), Box<dyn Error>> {
+        let mut source = String::new();
+        self.read_to_string(&mut source)?;
+
+        let mut last_end = 0;
+        for identifier in syntax::identifiers(&source, language) {
+            if !kinds.is_empty() && !kinds.contains(&identifier.kind) {
+                continue;
+            }
+
+            if let Some(converted) = convert_token(identifier.name, from_to_cases, dictionary) {
+                output.write_all(&source.as_bytes()[last_end..identifier.span.start])?;
+                output.write_all(converted.as_bytes())?;
+                last_end = identifier.span.end;
+            }
+        }
+        output.write_all(&source.as_bytes()[last_end..])?;
+
+        Ok(())
+    }
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::project::IdentifierMap;
+    use crate::scan::Scanner;
     use std::io::BufReader;
     use indoc::indoc;
 
@@ -65,6 +133,31 @@ mod tests {
         assert_eq!("camel-case", "camelCase".convert_case_to(Case::KebabCase));
     }
 
+    #[test]
+    fn converts_flat_tokens_with_dictionary() {
+        let dictionary = Dictionary::english();
+
+        assert_eq!(convert_token("getusername", &[(Case::SnakeCase, Case::CamelCase)], None), None);
+        assert_eq!(convert_token("getusername", &[(Case::SnakeCase, Case::CamelCase)], Some(&dictionary)), Some(String::from("getUserName")));
+        assert_eq!(convert_token("MAXRETRYCOUNT", &[(Case::ShoutingSnakeCase, Case::ShoutingSnakeCase)], Some(&dictionary)), Some(String::from("MAX_RETRY_COUNT")));
+    }
+
+    #[test]
+    fn lossless_roundtrip() {
+        assert_eq!(check_roundtrip("userName", &[(Case::CamelCase, Case::SnakeCase)], None), None);
+    }
+
+    #[test]
+    fn lossy_roundtrip() {
+        let result = check_roundtrip("XMLHttpRequest", &[(Case::PascalCase, Case::SnakeCase)], None);
+
+        assert_eq!(result, Some(LossyConversion {
+            original: String::from("XMLHttpRequest"),
+            converted: String::from("xml_http_request"),
+            roundtrip: String::from("XmlHttpRequest"),
+        }));
+    }
+
     #[test]
     fn converts_single_line_single_case() -> Result<(), Box<dyn Error>> {
         // ARRANGE
@@ -74,7 +167,7 @@ mod tests {
         let mut output = vec![];
 
         // ACT
-        input.buffered_convert(&vec![(Case::SnakeCase, Case::CamelCase)], Box::new(&mut output))?;
+        input.buffered_convert(&[(Case::SnakeCase, Case::CamelCase)], None, &mut output)?;
         let output = String::from_utf8(output)?;
 
 
@@ -95,7 +188,7 @@ mod tests {
         let mut output = vec![];
 
         // ACT
-        input.buffered_convert(&vec![(Case::PascalCase, Case::KebabCase)], Box::new(&mut output))?;
+        input.buffered_convert(&[(Case::PascalCase, Case::KebabCase)], None, &mut output)?;
         let output = String::from_utf8(output)?;
 
 
@@ -124,7 +217,7 @@ mod tests {
             (Case::PascalCase, Case::KebabCase),
             (Case::CamelCase, Case::SnakeCase),
         ];
-        input.buffered_convert(&coversions, Box::new(&mut output))?;
+        input.buffered_convert(&coversions, None, &mut output)?;
         let output = String::from_utf8(output)?;
 
 
@@ -136,4 +229,60 @@ mod tests {
         "#}.trim());
         Ok(())
     }
+
+    #[test]
+    fn keeps_line_endings_like_mapped_conversion() -> Result<(), Box<dyn Error>> {
+        // ARRANGE
+        let source = "fooBar baz\r\nquxQuux\n";
+        let conversions = [(Case::CamelCase, Case::SnakeCase)];
+        let mut identifier_map = IdentifierMap::new();
+        identifier_map.add_source("input", source, &Scanner::Whitespace, &conversions, None)?;
+        let mut output = vec![];
+
+        // ACT
+        BufReader::new(source.as_bytes()).buffered_convert(&conversions, None, &mut output)?;
+        let output = String::from_utf8(output)?;
+
+        // ASSERT
+        assert_eq!(output, "foo_bar baz\r\nqux_quux\n");
+        assert_eq!(output, identifier_map.apply(source, &Scanner::Whitespace)?);
+        Ok(())
+    }
+
+    #[test]
+    fn converts_only_identifiers_of_given_kind() -> Result<(), Box<dyn Error>> {
+        // ARRANGE
+        let mut input = BufReader::new(indoc! {r#"
+            struct UserAccount;
+
+            fn getUser(userId: u32) -> UserAccount {
+                // getUser loads an account
+                println!("getUser");
+                loadAccount(userId)
+            }
+        "#}.as_bytes());
+        let mut output = vec![];
+
+        // ACT
+        input.syntax_convert(
+            Language::Rust,
+            &[IdentifierKind::Function],
+            &[(Case::CamelCase, Case::SnakeCase), (Case::PascalCase, Case::SnakeCase)],
+            None,
+            &mut output,
+        )?;
+        let output = String::from_utf8(output)?;
+
+        // ASSERT
+        assert_eq!(output, indoc! {r#"
+            struct UserAccount;
+
+            fn get_user(userId: u32) -> UserAccount {
+                // getUser loads an account
+                println!("getUser");
+                load_account(userId)
+            }
+        "#});
+        Ok(())
+    }
 }
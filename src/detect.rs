@@ -14,6 +14,7 @@ pub trait CaseDetect {
 }
 
 impl CaseDetect for Case {
+    #[allow(clippy::needless_return)]
     fn detect(token: &str) -> Result<Option<Case>, DetectError> {
         if token.chars().any(|c| c.is_whitespace()) {
             return Err(DetectError::InvalidInputError(String::from(token)));
@@ -0,0 +1,143 @@
+use crate::words::{self, Word};
+use std::collections::HashSet;
+use std::io::{self, BufRead};
+
+const ENGLISH_WORDS: &str = include_str!("english_words.txt");
+
+/// A set of known words used to split tokens that have no word boundaries,
+/// such as `getusername` or `MAXRETRYCOUNT`
+#[derive(Debug, Default)]
+pub struct Dictionary {
+    words: HashSet<String>,
+    max_word_length: usize,
+}
+
+impl Dictionary {
+    /// A dictionary with a bundled list of common English words
+    pub fn english() -> Self {
+        let mut dictionary = Dictionary::default();
+        for word in ENGLISH_WORDS.lines() {
+            dictionary.insert(word);
+        }
+        dictionary
+    }
+
+    pub fn insert(&mut self, word: &str) {
+        let word = word.trim().to_lowercase();
+        if !word.is_empty() {
+            self.max_word_length = self.max_word_length.max(word.len());
+            self.words.insert(word);
+        }
+    }
+
+    /// Adds the words of a vocabulary with one word per line
+    pub fn extend_from<T: BufRead>(&mut self, vocabulary: &mut T) -> io::Result<()> {
+        for line in vocabulary.lines() {
+            self.insert(&line?);
+        }
+        Ok(())
+    }
+
+    /// Splits `text` into the fewest known words that cover all of it, returning
+    /// the length of each of them. Returns `None` if it can't be covered.
+    fn segment(&self, text: &str) -> Option<Vec<usize>> {
+        let text = text.to_lowercase();
+        // For each prefix length, the fewest words that cover it and the length of the last one
+        let mut best: Vec<Option<(usize, usize)>> = vec![None; text.len() + 1];
+        best[0] = Some((0, 0));
+
+        for end in 1..=text.len() {
+            for start in end.saturating_sub(self.max_word_length)..end {
+                let Some((count, _)) = best[start] else { continue };
+                if !self.words.contains(&text[start..end]) {
+                    continue;
+                }
+                if best[end].map(|(best_count, _)| count + 1 < best_count).unwrap_or(true) {
+                    best[end] = Some((count + 1, end - start));
+                }
+            }
+        }
+
+        let mut lengths = Vec::new();
+        let mut end = text.len();
+        while end > 0 {
+            let (_, length) = best[end]?;
+            lengths.push(length);
+            end -= length;
+        }
+        lengths.reverse();
+        Some(lengths)
+    }
+
+    /// Like `words::split_words`, but also splits words that are all lowercase
+    /// or all uppercase letters into the known words they are made of
+    pub fn split_words(&self, token: &str) -> Vec<Word> {
+        words::split_words(token).into_iter()
+                                 .flat_map(|word| self.split_flat_word(word))
+                                 .collect()
+    }
+
+    fn split_flat_word(&self, word: Word) -> Vec<Word> {
+        let is_flat = word.text.chars().all(|c| c.is_ascii_lowercase())
+                   || word.text.chars().all(|c| c.is_ascii_uppercase());
+
+        if !is_flat {
+            return vec![word];
+        }
+
+        match self.segment(&word.text) {
+            Some(lengths) if lengths.len() > 1 => {
+                let mut start = 0;
+                lengths.into_iter()
+                       .map(|length| {
+                           let text = String::from(&word.text[start..start + length]);
+                           let span = word.span.start + start..word.span.start + start + length;
+                           start += length;
+                           Word { text, span }
+                       })
+                       .collect()
+            },
+            _ => vec![word],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::case::Case;
+    use crate::words::join_words;
+
+    #[test]
+    fn splits_flat_tokens() {
+        let dictionary = Dictionary::english();
+
+        assert_eq!(join_words(&dictionary.split_words("getusername"), Case::CamelCase), "getUserName");
+        assert_eq!(join_words(&dictionary.split_words("MAXRETRYCOUNT"), Case::ShoutingSnakeCase), "MAX_RETRY_COUNT");
+    }
+
+    #[test]
+    fn keeps_word_spans() {
+        let dictionary = Dictionary::english();
+
+        let words = dictionary.split_words("the_usercount");
+
+        assert_eq!(words.iter().map(|word| word.span.clone()).collect::<Vec<_>>(), vec![0..3, 4..8, 8..13]);
+    }
+
+    #[test]
+    fn leaves_unknown_words_alone() {
+        let dictionary = Dictionary::english();
+
+        assert_eq!(dictionary.split_words("frobnicatewidget").len(), 1);
+    }
+
+    #[test]
+    fn uses_user_vocabulary() -> io::Result<()> {
+        let mut dictionary = Dictionary::english();
+        dictionary.extend_from(&mut "frobnicate\n".as_bytes())?;
+
+        assert_eq!(join_words(&dictionary.split_words("frobnicatewidget"), Case::SnakeCase), "frobnicate_widget");
+        Ok(())
+    }
+}
@@ -0,0 +1,186 @@
+use git2::{DiffOptions, Repository};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Component, Path, PathBuf};
+use thiserror::Error;
+
+/// The lines added to each file, by their 1-based number in the new version of the file
+pub type AddedLines = BTreeMap<PathBuf, BTreeSet<usize>>;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum DiffError {
+    #[error("invalid hunk header at line {0} of the diff: '{1}'")]
+    InvalidHunkHeaderError(usize, String),
+}
+
+/// Finds the lines added by a unified diff, such as the output of `git diff`.
+/// Paths lose the `b/` prefix that git adds and are [normalized](normalize),
+/// and deleted files are left out
+pub fn added_lines(diff: &str) -> Result<AddedLines, DiffError> {
+    let mut result = AddedLines::new();
+    let mut file: Option<PathBuf> = None;
+    let mut hunk = Hunk::default();
+
+    for (index, line) in diff.lines().enumerate() {
+        if hunk.old_left > 0 || hunk.new_left > 0 {
+            // Lines of a hunk may look like headers, such as an added "++ x" line
+            match line.chars().next() {
+                Some('+') => {
+                    if let Some(file) = &file {
+                        result.entry(file.clone()).or_default().insert(hunk.line_number);
+                    }
+                    hunk.line_number += 1;
+                    hunk.new_left = hunk.new_left.saturating_sub(1);
+                },
+                Some('-') => hunk.old_left = hunk.old_left.saturating_sub(1),
+                // "\ No newline at end of file"
+                Some('\\') => {},
+                _ => {
+                    hunk.line_number += 1;
+                    hunk.old_left = hunk.old_left.saturating_sub(1);
+                    hunk.new_left = hunk.new_left.saturating_sub(1);
+                },
+            }
+        } else if let Some(path) = line.strip_prefix("+++ ") {
+            // Some tools add a timestamp after a tab
+            let path = path.split('\t').next().unwrap_or_default();
+            file = (path != "/dev/null").then(|| normalize(Path::new(path.strip_prefix("b/").unwrap_or(path))));
+        } else if let Some(header) = line.strip_prefix("@@ ") {
+            hunk = Hunk::parse(header).ok_or_else(|| DiffError::InvalidHunkHeaderError(index + 1, String::from(line)))?;
+        }
+    }
+
+    Ok(result)
+}
+
+/// Where a hunk starts in the new version of the file, and how many of its lines
+/// of each version haven't been read yet
+#[derive(Default)]
+struct Hunk {
+    line_number: usize,
+    old_left: usize,
+    new_left: usize,
+}
+
+impl Hunk {
+    /// Parses a hunk header such as `-1,3 +1,4 @@`. A range without a count has one line
+    fn parse(header: &str) -> Option<Hunk> {
+        let range = |prefix: char| -> Option<(usize, usize)> {
+            let range = header.split_whitespace().find_map(|it| it.strip_prefix(prefix))?;
+            let mut parts = range.split(',');
+            let start = parts.next()?.parse().ok()?;
+            let count = parts.next().map_or(Some(1), |it| it.parse().ok())?;
+            Some((start, count))
+        };
+        let (_, old_count) = range('-')?;
+        let (new_start, new_count) = range('+')?;
+        Some(Hunk { line_number: new_start, old_left: old_count, new_left: new_count })
+    }
+}
+
+/// Finds the lines added to the working tree of the repository containing `path`,
+/// including uncommitted changes, since the commit `base`. Paths are [normalized](normalize)
+pub fn added_since(path: &Path, base: &str) -> Result<AddedLines, git2::Error> {
+    let repository = Repository::discover(path)?;
+    let base_tree = repository.revparse_single(base)?.peel_to_tree()?;
+    let workdir = repository.workdir().ok_or_else(|| git2::Error::from_str("the repository has no working tree"))?;
+
+    let mut options = DiffOptions::new();
+    options.include_untracked(true).recurse_untracked_dirs(true).show_untracked_content(true);
+    let diff = repository.diff_tree_to_workdir_with_index(Some(&base_tree), Some(&mut options))?;
+
+    let mut result = AddedLines::new();
+    diff.foreach(&mut |_, _| true, None, None, Some(&mut |delta, _, line| {
+        if let ('+', Some(line_number), Some(file)) = (line.origin(), line.new_lineno(), delta.new_file().path()) {
+            result.entry(normalize(&workdir.join(file))).or_default().insert(line_number as usize);
+        }
+        true
+    }))?;
+
+    Ok(result)
+}
+
+/// `path` without `.` components, with `..` resolved where possible and relative to
+/// the current directory if it's under it, so the same file compares equal however
+/// it's named (such as `./src/a.py`, `src/a.py` or its absolute path)
+pub fn normalize(path: &Path) -> PathBuf {
+    let current_dir = std::env::current_dir().ok();
+    let canonical_dir = current_dir.as_ref().and_then(|it| it.canonicalize().ok());
+    relative_to(&normalize_lexically(path), [current_dir, canonical_dir].iter().flatten())
+}
+
+fn normalize_lexically(path: &Path) -> PathBuf {
+    let mut result = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {},
+            Component::ParentDir if matches!(result.components().next_back(), Some(Component::Normal(_))) => {
+                result.pop();
+            },
+            component => result.push(component),
+        }
+    }
+    result
+}
+
+/// `path` relative to the first of `directories` it's under, or unchanged if there's none
+fn relative_to<'a>(path: &Path, mut directories: impl Iterator<Item = &'a PathBuf>) -> PathBuf {
+    directories.find_map(|directory| path.strip_prefix(directory).ok())
+               .map(Path::to_path_buf)
+               .unwrap_or_else(|| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn finds_added_lines() -> Result<(), DiffError> {
+        // ARRANGE
+        let diff = indoc! {"
+            diff --git a/src/app.py b/src/app.py
+            index 83db48f..bf269f4 100644
+            --- a/src/app.py
+            +++ b/src/app.py
+            @@ -1,3 +1,4 @@
+             def main():
+            -    userName = 1
+            +    user_name = 1
+            +    userId = 2
+                 return
+            @@ -10,0 +11,2 @@ def other():
+            +++ x
+            +    y = 2
+            diff --git a/old.py b/old.py
+            deleted file mode 100644
+            --- a/old.py
+            +++ /dev/null
+            @@ -1 +0,0 @@
+            -gone
+        "};
+
+        // ACT
+        let added = added_lines(diff)?;
+
+        // ASSERT
+        assert_eq!(added, AddedLines::from([(PathBuf::from("src/app.py"), BTreeSet::from([2, 3, 11, 12]))]));
+        Ok(())
+    }
+
+    #[test]
+    fn normalizes_paths() {
+        let root = PathBuf::from("/repo");
+
+        assert_eq!(normalize_lexically(Path::new("./src/../src/./a.py")), PathBuf::from("src/a.py"));
+        assert_eq!(normalize_lexically(Path::new("../a.py")), PathBuf::from("../a.py"));
+        assert_eq!(relative_to(Path::new("/repo/src/a.py"), [&root].into_iter()), PathBuf::from("src/a.py"));
+        assert_eq!(relative_to(Path::new("/other/a.py"), [&root].into_iter()), PathBuf::from("/other/a.py"));
+    }
+
+    #[test]
+    fn invalid_hunk_header() {
+        let result = added_lines("+++ b/a.py\n@@ nonsense @@\n");
+
+        assert_eq!(result, Err(DiffError::InvalidHunkHeaderError(2, String::from("@@ nonsense @@"))));
+    }
+}
@@ -0,0 +1,106 @@
+use crate::case::Case;
+use crate::convert::ConvertCaseTo;
+use crate::matchers::CaseMatcher;
+use crate::words::{split_words, Word};
+use std::fmt::{Formatter, Display, Result as FormatResult};
+use strum::IntoEnumIterator;
+use itertools::Itertools;
+
+/// Everything that goes into detecting and converting the case of a token
+#[derive(Debug)]
+pub struct Explanation {
+    pub token: String,
+    pub words: Vec<Word>,
+    pub matches: Vec<(Case, bool)>,
+    pub conversions: Vec<(Case, String)>,
+}
+
+impl Explanation {
+    pub fn of(token: &str) -> Self {
+        Explanation {
+            token: String::from(token),
+            words: split_words(token),
+            matches: Case::iter().map(|case| (case, case.matcher().is_match(token))).collect(),
+            conversions: Case::iter().map(|case| (case, token.convert_case_to(case))).collect(),
+        }
+    }
+
+    pub fn matching_cases(&self) -> Vec<Case> {
+        self.matches.iter()
+                    .filter(|(_, matches)| *matches)
+                    .map(|(case, _)| *case)
+                    .collect()
+    }
+}
+
+impl Display for Explanation {
+    fn fmt(&self, f: &mut Formatter) -> FormatResult {
+        writeln!(f, "Token: {}", self.token)?;
+        writeln!(f, "Words: {}", self.words.iter().map(|word| &word.text).join(", "))?;
+
+        writeln!(f, "Matching cases:")?;
+        for (case, matches) in &self.matches {
+            writeln!(f, "  {case}: {}", if *matches { "yes" } else { "no" })?;
+        }
+
+        let matching_cases = self.matching_cases();
+        if self.token.chars().any(|c| c.is_whitespace()) {
+            writeln!(f, "Detected case: none, it's not a token because it contains whitespace")?;
+        } else {
+            match matching_cases.as_slice() {
+                [] => writeln!(f, "Detected case: none, it doesn't match any case")?,
+                [case] => writeln!(f, "Detected case: {case}")?,
+                cases => writeln!(f, "Detected case: none, it's ambiguous between {}", cases.iter().join(", "))?,
+            }
+        }
+
+        write!(f, "Conversions:")?;
+        for (case, converted) in &self.conversions {
+            write!(f, "\n  {case}: {converted}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn explains_ambiguous_token() {
+        let explanation = Explanation::of("word");
+
+        assert_eq!(explanation.matching_cases(), vec![Case::CamelCase, Case::SnakeCase, Case::KebabCase]);
+        assert_eq!(explanation.words, vec![Word { text: String::from("word"), span: 0..4 }]);
+    }
+
+    #[test]
+    fn explanation_display() {
+        // ARRANGE
+        let explanation = Explanation::of("XMLHttpRequest");
+
+        // ACT
+        let display = format!("{}", explanation);
+
+        // ASSERT
+        assert_eq!(display, indoc! {"
+            Token: XMLHttpRequest
+            Words: XML, Http, Request
+            Matching cases:
+              PascalCase: yes
+              SHOUTING_SNAKE_CASE: no
+              camelCase: no
+              snake_case: no
+              kebab-case: no
+            Detected case: PascalCase
+            Conversions:
+              PascalCase: XmlHttpRequest
+              SHOUTING_SNAKE_CASE: XML_HTTP_REQUEST
+              camelCase: xmlHttpRequest
+              snake_case: xml_http_request
+              kebab-case: xml-http-request"
+        });
+    }
+}
@@ -0,0 +1,157 @@
+use crate::report::FrequencyCaseReport;
+use crate::syntax::{Language, IdentifierKind};
+use git2::{ObjectType, Oid, Repository, Sort, TreeWalkMode, TreeWalkResult};
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::error::Error;
+use std::path::Path;
+
+/// The cases used in the source files of a repository as of one commit
+#[derive(Debug)]
+pub struct Snapshot {
+    pub commit: String,
+    /// Time of the commit in seconds since the Unix epoch
+    pub time: i64,
+    pub report: Option<FrequencyCaseReport>,
+}
+
+/// Builds a report of the source files of every `every`th commit in the first-parent
+/// history of `revision`, oldest first, always including `revision` itself. Only files
+/// whose language is known from their extension are read, and if given, only those of
+/// `language` and under one of `paths`
+pub fn history(repository: &Path, revision: &str, every: usize, language: Option<Language>, kinds: &[IdentifierKind], paths: &[String]) -> Result<Vec<Snapshot>, Box<dyn Error>> {
+    let repository = Repository::discover(repository)?;
+    let mut walk = repository.revwalk()?;
+    walk.push(repository.revparse_single(revision)?.peel_to_commit()?.id())?;
+    walk.simplify_first_parent()?;
+    walk.set_sorting(Sort::TOPOLOGICAL)?;
+    let commits = walk.collect::<Result<Vec<Oid>, git2::Error>>()?;
+
+    // Most files don't change from one commit to the next, so each version is only scanned once
+    let mut file_reports: HashMap<Oid, Option<FrequencyCaseReport>> = HashMap::new();
+    let mut snapshots = Vec::new();
+
+    for commit_id in commits.into_iter().step_by(every.max(1)) {
+        let commit = repository.find_commit(commit_id)?;
+
+        let mut files = Vec::new();
+        commit.tree()?.walk(TreeWalkMode::PreOrder, |directory, entry| {
+            if entry.kind() == Some(ObjectType::Blob) {
+                files.push((format!("{directory}{}", entry.name().unwrap_or_default()), entry.id()));
+            }
+            TreeWalkResult::Ok
+        })?;
+
+        let mut file_ids = Vec::new();
+        for (path, file_id) in files {
+            let Some(file_language) = Language::from_path(&path) else { continue };
+            if language.is_some_and(|it| it != file_language) {
+                continue;
+            }
+            if !paths.is_empty() && !paths.iter().any(|prefix| Path::new(&path).starts_with(prefix)) {
+                continue;
+            }
+
+            if let Entry::Vacant(entry) = file_reports.entry(file_id) {
+                let blob = repository.find_blob(file_id)?;
+                let report = match std::str::from_utf8(blob.content()) {
+                    Ok(source) => FrequencyCaseReport::from_syntax(&mut source.as_bytes(), file_language, kinds)?,
+                    Err(_) => None,
+                };
+                entry.insert(report);
+            }
+            file_ids.push(file_id);
+        }
+
+        snapshots.push(Snapshot {
+            commit: commit_id.to_string(),
+            time: commit.time().seconds(),
+            report: FrequencyCaseReport::combine(file_ids.iter().filter_map(|id| file_reports[id].as_ref())),
+        });
+    }
+
+    snapshots.reverse();
+    Ok(snapshots)
+}
+
+/// The UTC date of a time in seconds since the Unix epoch, as YYYY-MM-DD
+pub fn date(seconds: i64) -> String {
+    // Converts days since the epoch to a civil date, as in
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let days = seconds.div_euclid(86_400) + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::case::Case;
+    use git2::Signature;
+    use std::fs;
+    use std::path::PathBuf;
+
+    /// A new repository in an empty directory only used by one test
+    fn test_repository(name: &str) -> Result<(PathBuf, Repository), Box<dyn Error>> {
+        let path = std::env::temp_dir().join(format!("casey-history-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path)?;
+        let repository = Repository::init(&path)?;
+        Ok((path, repository))
+    }
+
+    fn commit(repository: &Repository, time: i64, files: &[(&str, &str)]) -> Result<(), Box<dyn Error>> {
+        let root = repository.workdir().ok_or("bare repository")?;
+        let mut index = repository.index()?;
+        for (path, contents) in files {
+            fs::create_dir_all(root.join(path).parent().ok_or("no parent")?)?;
+            fs::write(root.join(path), contents)?;
+            index.add_path(Path::new(path))?;
+        }
+        index.write()?;
+
+        let tree = repository.find_tree(index.write_tree()?)?;
+        let signature = Signature::new("casey", "casey@example.com", &git2::Time::new(time, 0))?;
+        let parent = repository.head().ok().and_then(|head| head.peel_to_commit().ok());
+        repository.commit(Some("HEAD"), &signature, &signature, "commit", &tree, parent.as_ref().into_iter().collect::<Vec<_>>().as_slice())?;
+        Ok(())
+    }
+
+    #[test]
+    fn reports_cases_at_each_commit() -> Result<(), Box<dyn Error>> {
+        // ARRANGE
+        let (path, repository) = test_repository("series")?;
+        commit(&repository, 1_700_000_000, &[("src/a.py", "def getUser(userId): pass"), ("notes.txt", "user_name")])?;
+        commit(&repository, 1_700_086_400, &[("src/b.py", "def get_name(user_name): pass")])?;
+        commit(&repository, 1_700_172_800, &[("tools/c.py", "def get_role(user_role): pass")])?;
+
+        // ACT
+        let snapshots = history(&path, "HEAD", 1, None, &[], &[])?;
+        let sampled = history(&path, "HEAD", 2, Some(Language::Python), &[], &[String::from("src")])?;
+
+        // ASSERT
+        let frequencies = |snapshot: &Snapshot| snapshot.report.as_ref().map(|it| it.frequencies.clone()).unwrap_or_default();
+        assert_eq!(snapshots.len(), 3);
+        assert_eq!(date(snapshots[0].time), "2023-11-14");
+        assert_eq!(frequencies(&snapshots[0]), HashMap::from([(Case::CamelCase, 2)]));
+        assert_eq!(frequencies(&snapshots[2]), HashMap::from([(Case::CamelCase, 2), (Case::SnakeCase, 4)]));
+        assert_eq!(sampled.iter().map(|it| it.commit.clone()).collect::<Vec<_>>(), vec![snapshots[0].commit.clone(), snapshots[2].commit.clone()]);
+        assert_eq!(frequencies(&sampled[1]), HashMap::from([(Case::CamelCase, 2), (Case::SnakeCase, 2)]));
+        fs::remove_dir_all(path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn converts_times_to_dates() {
+        assert_eq!(date(0), "1970-01-01");
+        assert_eq!(date(951_825_600), "2000-02-29");
+        assert_eq!(date(-86_400), "1969-12-31");
+    }
+}
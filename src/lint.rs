@@ -0,0 +1,84 @@
+use crate::case::Case;
+use crate::project::Location;
+use crate::report;
+use crate::scan::{Scanner, ScanError};
+use std::collections::BTreeSet;
+use std::fmt::{Formatter, Display, Result as FormatResult};
+
+/// An occurrence of a token that doesn't use the expected case
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Violation {
+    pub token: String,
+    pub location: Location,
+    pub expected: Case,
+    /// The line the token is on, without leading and trailing whitespace
+    pub context: String,
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut Formatter) -> FormatResult {
+        write!(f, "{}: '{}' isn't {}", self.location, self.token, self.expected)
+    }
+}
+
+/// Every occurrence of a token of `source` that doesn't use the expected case, in
+/// order. If `lines` is given, only tokens starting on those lines are checked
+pub fn violations(origin: &str, source: &str, scanner: &Scanner, expected: Case, lines: Option<&BTreeSet<usize>>) -> Result<Vec<Violation>, ScanError> {
+    let source_lines: Vec<&str> = source.lines().collect();
+    let context = |location: &Location| location.line.checked_sub(1).and_then(|index| source_lines.get(index)).map(|line| String::from(line.trim())).unwrap_or_default();
+
+    let mut result: Vec<Violation> = report::non_conforming_tokens(origin, source, scanner, expected)?
+        .into_iter()
+        .flat_map(|(token, locations)| locations.into_iter().map(move |location| (token.clone(), location)))
+        .map(|(token, location)| Violation { token, context: context(&location), location, expected })
+        .filter(|violation| lines.is_none_or(|lines| lines.contains(&violation.location.line)))
+        .collect();
+
+    result.sort_by_key(|violation| (violation.location.line, violation.location.column));
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::syntax::Language;
+    use indoc::indoc;
+
+    #[test]
+    fn finds_violations_on_given_lines() -> Result<(), ScanError> {
+        // ARRANGE
+        let source = indoc! {"
+            def getUser(user_id):
+                userName = user_id
+                return userName
+        "};
+        let scanner = Scanner::Syntax(Language::Python, vec![]);
+
+        // ACT
+        let all = violations("a.py", source, &scanner, Case::SnakeCase, None)?;
+        let added = violations("a.py", source, &scanner, Case::SnakeCase, Some(&BTreeSet::from([2])))?;
+
+        // ASSERT
+        let describe = |violations: &[Violation]| violations.iter().map(|it| it.to_string()).collect::<Vec<_>>();
+        assert_eq!(describe(&all), vec![
+            "a.py:1:5: 'getUser' isn't snake_case",
+            "a.py:2:5: 'userName' isn't snake_case",
+            "a.py:3:12: 'userName' isn't snake_case",
+        ]);
+        assert_eq!(describe(&added), vec!["a.py:2:5: 'userName' isn't snake_case"]);
+        Ok(())
+    }
+
+    #[test]
+    fn skips_tokens_attached_to_punctuation() -> Result<(), ScanError> {
+        // ARRANGE
+        let source = "call(fooBar) and foo_bar, baz_qux.\nset userName\n";
+
+        // ACT
+        let result = violations("notes.txt", source, &Scanner::Whitespace, Case::SnakeCase, None)?;
+
+        // ASSERT
+        assert_eq!(result.iter().map(|it| it.to_string()).collect::<Vec<_>>(), vec!["notes.txt:2:5: 'userName' isn't snake_case"]);
+        Ok(())
+    }
+}
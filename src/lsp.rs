@@ -0,0 +1,271 @@
+use crate::case::Case;
+use crate::convert::ConvertCaseTo;
+use crate::detect::CaseDetect;
+use crate::lint::{self, Violation};
+use crate::report::{self, TieBreak};
+use crate::scan::{LineIndex, Scanner};
+use crate::syntax::{Language, IdentifierKind};
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::notification::{DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, Notification as _, PublishDiagnostics};
+use lsp_types::request::{CodeActionRequest, Request as _};
+use lsp_types::{CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, CodeActionProviderCapability, Diagnostic, DiagnosticSeverity, NumberOrString, Position, PublishDiagnosticsParams, Range, ServerCapabilities, TextDocumentSyncCapability, TextDocumentSyncKind, TextEdit, Uri, WorkspaceEdit};
+use std::collections::HashMap;
+use std::error::Error;
+use strum::IntoEnumIterator;
+
+/// How the server checks documents, given on the command line
+#[derive(Debug, Clone, Default)]
+pub struct Settings {
+    /// Case the tokens have to use (the most frequent one in each document if not given)
+    pub case: Option<Case>,
+    /// How to pick the most frequent case of a document when several cases are used the most
+    pub tie_break: TieBreak,
+    pub prefer: Vec<Case>,
+    /// Language of every document (inferred from the extension of each document if not
+    /// given, and documents without a known language aren't checked)
+    pub language: Option<Language>,
+    pub kinds: Vec<IdentifierKind>,
+}
+
+/// Runs a language server over stdin and stdout until the client shuts it down
+pub fn serve_stdio(settings: Settings) -> Result<(), Box<dyn Error>> {
+    let (connection, io_threads) = Connection::stdio();
+    serve(&connection, settings)?;
+    io_threads.join()?;
+    Ok(())
+}
+
+/// Answers the messages of `connection`, publishing the tokens that don't use the
+/// expected case as diagnostics of each open document and offering to convert them
+pub fn serve(connection: &Connection, settings: Settings) -> Result<(), Box<dyn Error>> {
+    let capabilities = ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+        ..ServerCapabilities::default()
+    };
+    connection.initialize(serde_json::to_value(capabilities)?)?;
+
+    let mut server = Server { settings, documents: HashMap::new() };
+    for message in &connection.receiver {
+        match message {
+            Message::Request(request) => {
+                if connection.handle_shutdown(&request)? {
+                    break;
+                }
+                connection.sender.send(Message::Response(server.respond(request)))?;
+            },
+            Message::Notification(notification) => {
+                if let Some(diagnostics) = server.update(notification) {
+                    connection.sender.send(Message::Notification(Notification::new(String::from(PublishDiagnostics::METHOD), diagnostics)))?;
+                }
+            },
+            Message::Response(_) => {},
+        }
+    }
+    Ok(())
+}
+
+struct Server {
+    settings: Settings,
+    /// The violations of each open document along with their ranges, which are only
+    /// found again when the document changes
+    documents: HashMap<Uri, Vec<(Violation, Range)>>,
+}
+
+impl Server {
+    /// Keeps track of the open documents, returning the diagnostics to publish for
+    /// the document the notification is about, if any
+    fn update(&mut self, notification: Notification) -> Option<PublishDiagnosticsParams> {
+        let (uri, source) = match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params: lsp_types::DidOpenTextDocumentParams = serde_json::from_value(notification.params).ok()?;
+                (params.text_document.uri, params.text_document.text)
+            },
+            DidChangeTextDocument::METHOD => {
+                // The documents are synchronized in full, so the last change has the whole text
+                let params: lsp_types::DidChangeTextDocumentParams = serde_json::from_value(notification.params).ok()?;
+                (params.text_document.uri, params.content_changes.into_iter().last()?.text)
+            },
+            DidCloseTextDocument::METHOD => {
+                let params: lsp_types::DidCloseTextDocumentParams = serde_json::from_value(notification.params).ok()?;
+                self.documents.remove(&params.text_document.uri);
+                return Some(PublishDiagnosticsParams::new(params.text_document.uri, vec![], None));
+            },
+            _ => return None,
+        };
+
+        let line_index = LineIndex::new(&source);
+        let violations: Vec<(Violation, Range)> = self.violations(&uri, &source)
+            .into_iter()
+            .map(|violation| {
+                let range = range_of(&source, &line_index, &violation);
+                (violation, range)
+            })
+            .collect();
+        let diagnostics = violations.iter().map(|(violation, range)| diagnostic(violation, *range)).collect();
+        self.documents.insert(uri.clone(), violations);
+        Some(PublishDiagnosticsParams::new(uri, diagnostics, None))
+    }
+
+    fn respond(&self, request: Request) -> Response {
+        if request.method != CodeActionRequest::METHOD {
+            return Response::new_err(request.id, ErrorCode::MethodNotFound as i32, format!("unsupported request {}", request.method));
+        }
+        let params: CodeActionParams = match serde_json::from_value(request.params) {
+            Ok(params) => params,
+            Err(err) => return Response::new_err(request.id, ErrorCode::InvalidParams as i32, err.to_string()),
+        };
+
+        let uri = params.text_document.uri;
+        let actions = match self.documents.get(&uri) {
+            Some(violations) => code_actions(&uri, violations, params.range),
+            None => vec![],
+        };
+        Response::new_ok(request.id, actions)
+    }
+
+    /// The tokens of a document that don't use the expected case. Documents without
+    /// a language, that can't be scanned, or have no primarily used case, have none
+    fn violations(&self, uri: &Uri, source: &str) -> Vec<Violation> {
+        // Splitting other documents on whitespace would leave punctuation in the tokens
+        let Some(language) = self.settings.language.or_else(|| Language::from_path(uri.path().as_str())) else {
+            return vec![];
+        };
+        let scanner = Scanner::Syntax(language, self.settings.kinds.clone());
+        let expected = match self.settings.case {
+            Some(case) => Some(case),
+            None => report::main_case(&[(source, &scanner)], self.settings.tie_break, &self.settings.prefer).ok().flatten(),
+        };
+
+        expected.and_then(|expected| lint::violations(uri.as_str(), source, &scanner, expected, None).ok())
+                .unwrap_or_default()
+    }
+}
+
+fn diagnostic(violation: &Violation, range: Range) -> Diagnostic {
+    Diagnostic {
+        range,
+        severity: Some(DiagnosticSeverity::WARNING),
+        code: Some(NumberOrString::String(violation.expected.to_string())),
+        source: Some(String::from("casey")),
+        message: format!("'{}' isn't {}", violation.token, violation.expected),
+        ..Diagnostic::default()
+    }
+}
+
+/// Conversions of the violations within `range` to every case, starting with the
+/// expected case, which fixes the diagnostic of the violation
+fn code_actions(uri: &Uri, violations: &[(Violation, Range)], range: Range) -> Vec<CodeActionOrCommand> {
+    let mut actions = Vec::new();
+
+    for (violation, token_range) in violations {
+        let token_range = *token_range;
+        // Only a token that's a single identifier with a case can be replaced without touching its surroundings
+        if token_range.end < range.start || token_range.start > range.end || !matches!(Case::detect(&violation.token), Ok(Some(_))) {
+            continue;
+        }
+
+        let cases = std::iter::once(violation.expected).chain(Case::iter().filter(|case| *case != violation.expected));
+        for case in cases {
+            let converted = violation.token.convert_case_to(case);
+            if converted == violation.token {
+                continue;
+            }
+
+            let is_fix = case == violation.expected;
+            actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                title: format!("Convert '{}' to {case}", violation.token),
+                kind: Some(if is_fix { CodeActionKind::QUICKFIX } else { CodeActionKind::REFACTOR_REWRITE }),
+                diagnostics: is_fix.then(|| vec![diagnostic(violation, token_range)]),
+                edit: Some(WorkspaceEdit::new(HashMap::from([(uri.clone(), vec![TextEdit::new(token_range, converted)])]))),
+                is_preferred: Some(is_fix),
+                ..CodeAction::default()
+            }));
+        }
+    }
+    actions
+}
+
+/// Where the token of a violation is, with columns in UTF-16 code units as LSP
+/// expects by default, while violations count characters
+fn range_of(source: &str, line_index: &LineIndex, violation: &Violation) -> Range {
+    let line = violation.location.line.saturating_sub(1);
+    let line_start = line_index.line_start(line).unwrap_or(source.len());
+    let before: usize = source[line_start..].chars()
+                                            .take(violation.location.column.saturating_sub(1))
+                                            .map(char::len_utf16)
+                                            .sum();
+    let length: usize = violation.token.chars().map(char::len_utf16).sum();
+
+    Range::new(Position::new(line as u32, before as u32), Position::new(line as u32, (before + length) as u32))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_server::RequestId;
+    use serde_json::{json, Value};
+    use std::thread;
+
+    fn request(id: i32, method: &str, params: Value) -> Message {
+        Message::Request(Request::new(RequestId::from(id), String::from(method), params))
+    }
+
+    fn notification(method: &str, params: Value) -> Message {
+        Message::Notification(Notification::new(String::from(method), params))
+    }
+
+    #[test]
+    fn publishes_diagnostics_and_offers_conversions() -> Result<(), Box<dyn Error>> {
+        // ARRANGE
+        let (server, client) = Connection::memory();
+        let settings = Settings { case: Some(Case::SnakeCase), ..Settings::default() };
+        let server = thread::spawn(move || serve(&server, settings).map_err(|err| err.to_string()));
+
+        // ACT
+        client.sender.send(request(1, "initialize", json!({ "capabilities": {} })))?;
+        client.receiver.recv()?;
+        client.sender.send(notification("initialized", json!({})))?;
+        client.sender.send(notification("textDocument/didOpen", json!({
+            "textDocument": { "uri": "file:///src/a.py", "languageId": "python", "version": 1, "text": "user_id = 1\nprint(\"😀\", userName)\n" },
+        })))?;
+        let Message::Notification(published) = client.receiver.recv()? else { panic!("expected diagnostics") };
+        client.sender.send(request(2, "textDocument/codeAction", json!({
+            "textDocument": { "uri": "file:///src/a.py" },
+            "range": { "start": { "line": 1, "character": 13 }, "end": { "line": 1, "character": 13 } },
+            "context": { "diagnostics": [] },
+        })))?;
+        let Message::Response(actions) = client.receiver.recv()? else { panic!("expected code actions") };
+        client.sender.send(request(3, "shutdown", Value::Null))?;
+        client.receiver.recv()?;
+        client.sender.send(notification("exit", Value::Null))?;
+
+        // ASSERT
+        assert_eq!(server.join().map_err(|_| "server panicked")?, Ok(()));
+        let diagnostics = &published.params["diagnostics"];
+        assert_eq!(diagnostics.as_array().map(Vec::len), Some(1));
+        assert_eq!(diagnostics[0]["message"], "'userName' isn't snake_case");
+        assert_eq!(diagnostics[0]["range"], json!({ "start": { "line": 1, "character": 12 }, "end": { "line": 1, "character": 20 } }));
+
+        let actions = actions.result.ok_or("no code actions")?;
+        assert_eq!(actions[0]["title"], "Convert 'userName' to snake_case");
+        assert_eq!(actions[0]["isPreferred"], true);
+        assert_eq!(actions[0]["edit"]["changes"]["file:///src/a.py"][0]["newText"], "user_name");
+        assert!(actions.as_array().ok_or("no code actions")?.iter().any(|it| it["title"] == "Convert 'userName' to SHOUTING_SNAKE_CASE"));
+        Ok(())
+    }
+
+    #[test]
+    fn skips_documents_without_a_language() {
+        // ARRANGE
+        let server = Server { settings: Settings { case: Some(Case::SnakeCase), ..Settings::default() }, documents: HashMap::new() };
+        let uri = |path: &str| path.parse::<Uri>().expect("valid uri");
+        let source = "call(fooBar) and userName\n";
+
+        // ACT & ASSERT
+        assert!(server.violations(&uri("file:///notes.txt"), source).is_empty());
+        let with_language = Server { settings: Settings { language: Some(Language::Python), ..server.settings.clone() }, documents: HashMap::new() };
+        let tokens: Vec<String> = with_language.violations(&uri("file:///notes.txt"), source).into_iter().map(|it| it.token).collect();
+        assert_eq!(tokens, vec!["fooBar", "userName"]);
+    }
+}
@@ -4,24 +4,63 @@ mod matchers;
 mod report;
 mod args;
 mod convert;
+mod syntax;
+mod scan;
+mod project;
+mod explain;
+mod words;
+mod dictionary;
+mod review;
+mod structured;
+mod table;
+mod shell;
+mod rename;
+mod stats;
+mod history;
+mod diff;
+mod lint;
+mod baseline;
+mod sarif;
+mod lsp;
 
 use clap::{Parser, CommandFactory};
-use args::{Args, Command, ReportType};
-use convert::BufferedConvert;
+use args::{Args, Command, ReportType, CollisionHandling, MainCaseScope};
+use convert::{BufferedConvert, SyntaxConvert};
 use std::fs::{File, OpenOptions};
 use std::error::Error;
-use std::io::{self, BufReader, BufRead, Write};
-use report::FrequencyCaseReport;
+use std::io::{self, BufReader, BufRead, Read, Write};
+use report::{FrequencyCaseReport, KeyCaseReport, CaseReportError, TieBreak, Threshold};
+use serde_json::json;
 use std::fmt::Display;
 use std::io::Cursor;
-use case::Case;
+use case::{Case, SourceCase, from_to_cases};
 use std::path::Path;
+use std::collections::HashMap;
+use strum::IntoEnumIterator;
+use syntax::Language;
+use scan::Scanner;
+use project::IdentifierMap;
+use explain::Explanation;
+use dictionary::Dictionary;
+use structured::{Format, KeyFilter};
+use table::{Dialect, TableFormat};
+use stats::ConfidenceLevel;
+use baseline::Baseline;
+
+/// Exit code of `detect` when no case is used clearly the most
+const MIXED_EXIT_CODE: i32 = 2;
 
 fn main() -> Result<(), Box<dyn Error>> {
     let args = Args::parse();
 
     match args.command {
-        Command::Detect { file, stdin: _, mut inline, main: _, report: print_report } => {
+        Command::Detect { file, stdin: _, filenames, mut inline, main: _, report: print_report, language, kind, format, max_depth, path, group_by, table, delimiter, column, shell, non_conforming, tie_break, prefer, min_share, min_tokens, confidence, json, sarif } => {
+            let language = resolve_language(language, &kind, &file);
+            let key_filter = KeyFilter { max_depth, paths: path };
+            let format = resolve_format(format, key_filter != KeyFilter::default() || group_by.is_some(), language.is_some() || table.is_some() || shell || filenames.is_some(), &file);
+            let dialect = resolve_dialect(table, delimiter, &column, &file);
+            let origin = input_origin(&file, &inline);
+
             let mut input_read: Box<dyn BufRead> = if let Some(file_name) = file {
                 Box::new(BufReader::new(File::open(file_name)?))
             } else if let Some(token) = inline.take() {
@@ -30,20 +69,143 @@ fn main() -> Result<(), Box<dyn Error>> {
                 Box::new(BufReader::new(io::stdin()))
             };
 
-            let report = FrequencyCaseReport::from(&mut input_read)?;
+            // Locating non-conforming tokens, or finding which tied case appears first, needs
+            // the whole input again after building the report
+            let mut source = None;
+            if (non_conforming.is_some() || tie_break == TieBreak::FirstOccurrence) && filenames.is_none() {
+                let mut whole_input = String::new();
+                input_read.read_to_string(&mut whole_input)?;
+                input_read = Box::new(BufReader::new(Cursor::new(whole_input.clone().into_bytes())));
+                source = Some(whole_input);
+            }
+
+            let mut key_report = None;
+            let report = if let Some(root) = &filenames {
+                FrequencyCaseReport::from_filenames(Path::new(root))?
+            } else if let Some(language) = language {
+                FrequencyCaseReport::from_syntax(&mut input_read, language, &kind)?
+            } else if let Some(format) = format {
+                let mut source = String::new();
+                input_read.read_to_string(&mut source)?;
+
+                let grouped = KeyCaseReport::from_keys(&source, format, &key_filter, group_by.unwrap_or(report::KeyGrouping::Depth))?;
+                for mixed_object in &grouped.mixed_objects {
+                    eprintln!("Mixed cases: {mixed_object}");
+                }
+                if group_by.is_some() {
+                    key_report = Some(grouped);
+                }
+                FrequencyCaseReport::from_keys(&mut source.as_bytes(), format, &key_filter)?
+            } else if let Some(dialect) = &dialect {
+                FrequencyCaseReport::from_table(&mut input_read, dialect, &column)?
+            } else if shell {
+                FrequencyCaseReport::from_variables(&mut input_read)?
+            } else {
+                FrequencyCaseReport::from(&mut input_read)?
+            };
+            let threshold = Threshold { min_share: min_share.unwrap_or(0.0), min_tokens: min_tokens.unwrap_or(0) };
+            let preferred = match (&filenames, &source) {
+                _ if tie_break != TieBreak::FirstOccurrence => prefer,
+                (Some(root), _) => report::first_seen_in_filenames(Path::new(root))?,
+                (None, Some(source)) => report::first_seen(&[(source, &select_scanner(language, &kind, format, &key_filter, &dialect, &column, shell))])?,
+                (None, None) => vec![],
+            };
+            let dominant = |report: &FrequencyCaseReport| report.dominant(tie_break, &preferred, threshold);
+            let main_case = report.as_ref().and_then(|it| dominant(it).ok());
+
+            let mut outliers = None;
+            if let Some(root) = &filenames {
+                match non_conforming.flatten().or(main_case) {
+                    Some(expected_case) => outliers = Some(report::filename_outliers(Path::new(root), expected_case)?),
+                    None => eprintln!("Unable to find outliers without a primarily used case."),
+                }
+            }
+
+            // Without an expected case, that's only reported after printing the main case or report
+            let mut non_conforming_tokens = None;
+            let mut missing_expected_case = false;
+            if let (Some(expected_case), Some(source)) = (non_conforming, source) {
+                match expected_case.or(main_case) {
+                    Some(expected_case) => {
+                        let scanner = select_scanner(language, &kind, format, &key_filter, &dialect, &column, shell);
+                        if sarif {
+                            let violations = lint::violations(&origin, &source, &scanner, expected_case, None)?;
+                            println!("{}", serde_json::to_string_pretty(&sarif::log(&violations, &std::env::current_dir()?))?);
+                            return Ok(());
+                        }
+                        non_conforming_tokens = Some(report::non_conforming_tokens(&origin, &source, &scanner, expected_case)?);
+                    },
+                    None if sarif => {
+                        eprintln!("Unable to find non-conforming tokens without a primarily used case.");
+                        std::process::exit(1);
+                    },
+                    None => missing_expected_case = true,
+                }
+            }
+            let exit_if_missing_expected_case = || if missing_expected_case {
+                eprintln!("Unable to find non-conforming tokens without a primarily used case.");
+                std::process::exit(1);
+            };
 
+            if json {
+                let Some(report) = report else {
+                    eprintln!("Unable to detect cases.");
+                    std::process::exit(1);
+                };
+
+                let report_type = print_report.map(|it| it.unwrap_or(ReportType::Percentage));
+                let main = dominant(&report);
+                let mixed = main.as_ref().is_err_and(CaseReportError::is_mixed);
+                let mut output = case_summary(&report, report_type, main, confidence)?;
+                if let Some(key_report) = &key_report {
+                    let groups = key_report.groups.iter()
+                        .map(|(group, report)| {
+                            let mut summary = case_summary(report, report_type, dominant(report), confidence)?;
+                            summary["group"] = json!(group);
+                            Ok(summary)
+                        })
+                        .collect::<Result<Vec<_>, CaseReportError>>()?;
+                    output["groups"] = json!(groups);
+                }
+                if let Some(outliers) = &outliers {
+                    output["outliers"] = json!(outliers.iter().map(|it| it.display().to_string()).collect::<Vec<_>>());
+                }
+                if let Some(tokens) = &non_conforming_tokens {
+                    output["non_conforming"] = json!(tokens.iter()
+                        .map(|(token, locations)| json!({ "token": token, "locations": locations.iter().map(|it| it.to_string()).collect::<Vec<_>>() }))
+                        .collect::<Vec<_>>());
+                }
+                println!("{}", serde_json::to_string_pretty(&output)?);
+                exit_if_missing_expected_case();
+                if mixed {
+                    std::process::exit(MIXED_EXIT_CODE);
+                }
+                return Ok(());
+            }
+
+            let mut mixed = false;
             if let Some(print_report) = print_report {
                 // Print the full report
                 let report_type = print_report.unwrap_or(ReportType::Percentage);
 
                 if let Some(report) = report {
-                    let report: Box<dyn Display> = match report_type {
-                        ReportType::Frequency => Box::new(report),
-                        ReportType::Proportion => Box::new(report.proportions()),
-                        ReportType::Percentage => Box::new(report.proportions().as_percentages()?),
-                    };
+                    println!("{}", report_display(&report, report_type)?);
+                    if let Some(level) = confidence {
+                        println!("\n{}", report.confidence(level, main_case));
+                    }
 
-                    println!("{}", report);
+                    for (group, report) in key_report.iter().flat_map(|it| &it.groups) {
+                        println!("\n{group}:");
+                        for line in report_display(report, report_type)?.to_string().lines() {
+                            println!("  {line}");
+                        }
+                        if let Some(level) = confidence {
+                            println!();
+                            for line in report.confidence(level, dominant(report).ok()).to_string().lines() {
+                                println!("  {line}");
+                            }
+                        }
+                    }
                 } else {
                     eprintln!("Unable to detect cases.");
                     std::process::exit(1);
@@ -51,18 +213,56 @@ fn main() -> Result<(), Box<dyn Error>> {
 
             } else {
                 // Print only the main case
-                if let Some(report) = report {
-                    match report.main() {
+                if let Some(report) = &report {
+                    match dominant(report) {
                         Ok(main_case) => println!("{}", main_case),
+                        Err(err) if err.is_mixed() => {
+                            println!("mixed");
+                            eprintln!("No case is used clearly the most: {err}.");
+                            mixed = true;
+                        },
                         Err(err) => eprintln!("Unable to detect a primarily used case: {}.", err),
                     }
+
+                    for (group, report) in key_report.iter().flat_map(|it| &it.groups) {
+                        match dominant(report) {
+                            Ok(main_case) => println!("{group}: {main_case}"),
+                            Err(err) if err.is_mixed() => println!("{group}: mixed"),
+                            Err(err) => eprintln!("Unable to detect a primarily used case in {group}: {err}."),
+                        }
+                    }
                 } else {
                     eprintln!("Unable to detect a primarily used case.");
                     std::process::exit(1);
                 }
             }
+
+            for outlier in outliers.iter().flatten() {
+                println!("Outlier: {}", outlier.display());
+            }
+
+            for (token, locations) in non_conforming_tokens.iter().flatten() {
+                println!("Non-conforming: {token} at {}", locations.iter().map(|it| it.to_string()).collect::<Vec<_>>().join(", "));
+            }
+            exit_if_missing_expected_case();
+
+            if mixed {
+                std::process::exit(MIXED_EXIT_CODE);
+            }
         },
-        Command::Convert { file, stdin: _, mut inline, from, to, stdout: _, output } => {
+        Command::Convert { file, stdin: _, mut inline, from, to, to_main, tie_break, prefer, stdout: _, output, language, kind, format, max_depth, path, table, delimiter, column, shell, on_collision, verify_roundtrip, dictionary, vocabulary, interactive } => {
+            let language = resolve_language(language, &kind, &file);
+            let key_filter = KeyFilter { max_depth, paths: path };
+            let format = resolve_format(format, key_filter != KeyFilter::default(), language.is_some() || table.is_some() || shell, &file);
+            let dialect = resolve_dialect(table, delimiter, &column, &file);
+            if interactive && file.is_none() && inline.is_none() {
+                Args::command().error(
+                    clap::error::ErrorKind::ArgumentConflict,
+                    "--interactive reads answers from stdin, so the input must be a --file or --inline"
+                ).exit();
+            }
+            let origin = input_origin(&file, &inline);
+
             let mut input: Box<dyn BufRead> = if let Some(file_name) = file {
                 Box::new(BufReader::new(File::open(file_name)?))
             } else if let Some(token) = inline.take() {
@@ -71,32 +271,305 @@ fn main() -> Result<(), Box<dyn Error>> {
                 Box::new(BufReader::new(io::stdin()))
             };
 
-            if from.len() != to.len() {
-                Args::command().error(
-                    clap::error::ErrorKind::TooFewValues,
-                    "You must provide one --to argument for each --from argument"
-                ).exit();
-            }
+            let mut conversions = if to_main { Vec::new() } else { conversions(from, to) };
+            let dictionary = load_dictionary(dictionary, &vocabulary)?;
+
+            // The main case, collisions and lossy conversions can only be found by looking at the whole input first
+            let mut converted = None;
+            if to_main || on_collision != CollisionHandling::Ignore || verify_roundtrip || interactive || format.is_some() || dialect.is_some() || shell {
+                let mut source = String::new();
+                input.read_to_string(&mut source)?;
 
-            let conversions: Vec<(Case, Case)> = from.into_iter().zip(to.into_iter()).collect();
+                let scanner = select_scanner(language, &kind, format, &key_filter, &dialect, &column, shell);
+                if to_main {
+                    conversions = main_case_conversions(&[(&source, &scanner)], tie_break.unwrap_or_default(), &prefer)?;
+                }
+
+                if verify_roundtrip {
+                    let mut lossless = true;
+                    let line_index = scan::LineIndex::new(&source);
+                    for token in scanner.tokens(&source)? {
+                        if let Some(lossy_conversion) = convert::check_roundtrip(token.text, &conversions, dictionary.as_ref()) {
+                            let (line, column) = line_index.line_column(token.span.start);
+                            eprintln!("Lossy conversion: {lossy_conversion} at {origin}:{line}:{column}");
+                            lossless = false;
+                        }
+                    }
+                    if !lossless {
+                        eprintln!("Not converting anything because some conversions can't be undone.");
+                        std::process::exit(1);
+                    }
+                }
+
+                let mut identifier_map = IdentifierMap::new();
+                identifier_map.add_source(&origin, &source, &scanner, &conversions, dictionary.as_ref())?;
+                handle_collisions(&mut identifier_map, on_collision);
+
+                if interactive {
+                    let changes = identifier_map.changes(&source, &scanner)?;
+                    converted = Some(review::review(&origin, &source, &changes, &mut io::stdin().lock(), &mut io::stderr())?);
+                } else if on_collision == CollisionHandling::Disambiguate || !matches!(scanner, Scanner::Whitespace | Scanner::Syntax(..)) {
+                    // Documents and tables have no streaming conversion, so they always go through the map
+                    converted = Some(identifier_map.apply(&source, &scanner)?);
+                }
+                input = Box::new(BufReader::new(Cursor::new(source.into_bytes())));
+            }
 
             let mut file_write;
             let mut stdout_write;
 
-            let output: Box<&mut dyn Write> = if let Some(file_name) = output {
+            let output: &mut dyn Write = if let Some(file_name) = output {
                 file_write = if Path::new(&file_name).is_file() {
                     OpenOptions::new().append(true).open(file_name)?
                 } else {
                     File::create(file_name)?
                 };
-                Box::new(&mut file_write)
+                &mut file_write
             } else {
                 stdout_write = io::stdout();
-                Box::new(&mut stdout_write)
+                &mut stdout_write
+            };
+
+            if let Some(converted) = converted {
+                output.write_all(converted.as_bytes())?;
+            } else if let Some(language) = language {
+                input.syntax_convert(language, &kind, &conversions, dictionary.as_ref(), output)?;
+            } else {
+                input.buffered_convert(&conversions, dictionary.as_ref(), output)?;
+            }
+
+        },
+        Command::Refactor { files, from, to, to_main, scope, tie_break, prefer, language, kind, dry_run, on_collision, dictionary, vocabulary } => {
+            let conversions = if to_main { Vec::new() } else { conversions(from, to) };
+            let dictionary = load_dictionary(dictionary, &vocabulary)?;
+
+            let mut sources = Vec::new();
+            for file_name in files {
+                let scanner = match resolve_language(language, &kind, &Some(file_name.clone())) {
+                    Some(language) => Scanner::Syntax(language, kind.clone()),
+                    None => Scanner::Whitespace,
+                };
+                let source = std::fs::read_to_string(&file_name)?;
+                sources.push((file_name, scanner, source));
+            }
+
+            // Files converted to their own main case don't share renames
+            let groups: Vec<&[(String, Scanner, String)]> = if to_main && scope == Some(MainCaseScope::File) {
+                sources.chunks(1).collect()
+            } else {
+                vec![&sources]
+            };
+
+            let mut converted = Vec::new();
+            for group in groups {
+                let conversions = if to_main {
+                    main_case_conversions(&group.iter().map(|(_, scanner, source)| (source.as_str(), scanner)).collect::<Vec<_>>(), tie_break.unwrap_or_default(), &prefer)?
+                } else {
+                    conversions.clone()
+                };
+
+                let mut identifier_map = IdentifierMap::new();
+                for (file_name, scanner, source) in group {
+                    identifier_map.add_source(file_name, source, scanner, &conversions, dictionary.as_ref())?;
+                }
+
+                if dry_run {
+                    // Nothing is renamed anyway, so there's nothing to abort
+                    let on_collision = if on_collision == CollisionHandling::Abort { CollisionHandling::Warn } else { on_collision };
+                    handle_collisions(&mut identifier_map, on_collision);
+                    for (old, new) in &identifier_map.renames {
+                        println!("{old} -> {new}");
+                    }
+                } else {
+                    handle_collisions(&mut identifier_map, on_collision);
+                    for (file_name, scanner, source) in group {
+                        converted.push((file_name.clone(), identifier_map.apply(source, scanner)?));
+                    }
+                }
+            }
+
+            if !dry_run {
+                project::replace_all(&converted)?;
+            }
+        },
+        Command::Rename { paths, from, to, recursive, target, dry_run, git, dictionary, vocabulary } => {
+            let conversions = conversions(from, to);
+            let dictionary = load_dictionary(dictionary, &vocabulary)?;
+
+            let renames = rename::plan(&paths, recursive, target, &conversions, dictionary.as_ref())?;
+            let collisions = rename::collisions(&renames)?;
+            for collision in &collisions {
+                eprintln!("Collision: {}", collision);
+            }
+
+            if dry_run {
+                for rename in &renames {
+                    println!("{} -> {}", rename.from.display(), rename.to.display());
+                }
+            } else if !collisions.is_empty() {
+                eprintln!("Not renaming anything because of collisions.");
+                std::process::exit(1);
+            } else {
+                rename::apply(&renames, git)?;
+            }
+        },
+        Command::Lint { files, case, tie_break, prefer, language, kind, diff: diff_file, base, baseline: baseline_file, write_baseline, sarif } => {
+            let added_lines = if let Some(diff_file) = diff_file {
+                let mut diff = String::new();
+                if diff_file == "-" {
+                    io::stdin().read_to_string(&mut diff)?;
+                } else {
+                    File::open(diff_file)?.read_to_string(&mut diff)?;
+                }
+                Some(diff::added_lines(&diff)?)
+            } else if let Some(base) = base {
+                Some(diff::added_since(Path::new("."), &base)?)
+            } else {
+                None
+            };
+
+            // With a diff, only the changed files are checked. Paths are normalized so
+            // they match those of the diff however they were written
+            let files: Vec<String> = files.iter().map(|it| diff::normalize(Path::new(it)).display().to_string()).collect();
+            // Files taken from the diff may have been deleted or be binary, so those that can't be read are skipped
+            let files_from_diff = files.is_empty() && added_lines.is_some();
+            let files: Vec<String> = match &added_lines {
+                Some(added_lines) if files.is_empty() => added_lines.keys().map(|path| path.display().to_string()).collect(),
+                Some(added_lines) => files.into_iter()
+                                          .filter(|file_name| {
+                                              let changed = added_lines.contains_key(Path::new(file_name));
+                                              if !changed {
+                                                  eprintln!("Not checking {file_name}, which has no added lines.");
+                                              }
+                                              changed
+                                          })
+                                          .collect(),
+                None => files,
+            };
+
+            let mut sources = Vec::new();
+            for file_name in files {
+                let scanner = match language.or_else(|| Language::from_path(&file_name)) {
+                    Some(language) => Scanner::Syntax(language, kind.clone()),
+                    None => Scanner::Whitespace,
+                };
+                let source = match std::fs::read_to_string(&file_name) {
+                    Ok(source) => source,
+                    Err(error) if files_from_diff => {
+                        eprintln!("Not checking {file_name}, which can't be read as text: {error}.");
+                        continue;
+                    },
+                    Err(error) => return Err(error.into()),
+                };
+                sources.push((file_name, scanner, source));
+            }
+
+            let baseline = match &baseline_file {
+                Some(baseline_file) => Some(Baseline::parse(&std::fs::read_to_string(baseline_file)?)?),
+                None => None,
             };
 
-            input.buffered_convert(&conversions, output)?;
+            // The whole files tell which case is expected, even if only some lines are checked,
+            // unless the baseline recorded the one expected when it was written
+            let expected = match case.or_else(|| baseline.as_ref().and_then(|it| it.case)) {
+                Some(case) => Some(case),
+                None => report::main_case(&sources.iter().map(|(_, scanner, source)| (source.as_str(), scanner)).collect::<Vec<_>>(), tie_break.unwrap_or_default(), &prefer)?,
+            };
+            let Some(expected) = expected else {
+                if !sources.is_empty() {
+                    eprintln!("Unable to lint without a --case or a primarily used case (several cases may be used the most, see --tie-break).");
+                    std::process::exit(1);
+                }
+                return Ok(());
+            };
 
+            let mut violations = Vec::new();
+            for (file_name, scanner, source) in &sources {
+                // Baseline entries are matched against whole files, so those on unchanged lines don't look fixed
+                let lines = match baseline {
+                    Some(_) => None,
+                    None => added_lines.as_ref().and_then(|it| it.get(Path::new(file_name))),
+                };
+                violations.extend(lint::violations(file_name, source, scanner, expected, lines)?);
+            }
+
+            if let Some(baseline_file) = write_baseline {
+                std::fs::write(&baseline_file, Baseline::from_violations(expected, &violations).to_json()? + "\n")?;
+                match violations.len() {
+                    1 => eprintln!("Recorded 1 violation in {baseline_file}."),
+                    count => eprintln!("Recorded {count} violations in {baseline_file}."),
+                }
+                return Ok(());
+            }
+
+            let mut fixed = Vec::new();
+            if let Some(baseline) = baseline {
+                let checked: Vec<String> = sources.iter().map(|(file_name, _, _)| file_name.clone()).collect();
+                (violations, fixed) = baseline.apply(violations, &checked);
+                if let Some(added_lines) = &added_lines {
+                    violations.retain(|violation| added_lines.get(Path::new(&violation.location.origin)).is_some_and(|lines| lines.contains(&violation.location.line)));
+                }
+            }
+
+            if sarif {
+                println!("{}", serde_json::to_string_pretty(&sarif::log(&violations, &std::env::current_dir()?))?);
+                for entry in &fixed {
+                    eprintln!("{entry} is fixed and can be removed from the baseline");
+                }
+            } else {
+                for violation in &violations {
+                    println!("{violation}");
+                }
+                for entry in &fixed {
+                    println!("{entry} is fixed and can be removed from the baseline");
+                }
+            }
+            if !violations.is_empty() {
+                match violations.len() {
+                    1 => eprintln!("Found 1 token that isn't {expected}."),
+                    count => eprintln!("Found {count} tokens that aren't {expected}."),
+                }
+                std::process::exit(1);
+            }
+        },
+        Command::Lsp { case, tie_break, prefer, language, kind } => {
+            lsp::serve_stdio(lsp::Settings { case, tie_break: tie_break.unwrap_or_default(), prefer, language, kinds: kind })?;
+        },
+        Command::History { repository, revision, every, language, kind, path, report: report_type, json } => {
+            let snapshots = history::history(Path::new(&repository), &revision, every as usize, language, &kind, &path)?;
+
+            if json {
+                let series = snapshots.iter()
+                    .map(|snapshot| {
+                        let mut entry = match &snapshot.report {
+                            Some(report) => case_summary(report, Some(report_type), report.main(), None)?,
+                            // Commits without any token have the same keys, with nothing in them
+                            None => json!({ "main": null, "mixed": false, "tied": [], "report": {} }),
+                        };
+                        entry["commit"] = json!(snapshot.commit);
+                        entry["date"] = json!(history::date(snapshot.time));
+                        Ok(entry)
+                    })
+                    .collect::<Result<Vec<_>, CaseReportError>>()?;
+                println!("{}", serde_json::to_string_pretty(&series)?);
+                return Ok(());
+            }
+
+            println!("commit,date,main,{}", Case::iter().map(|case| case.to_string()).collect::<Vec<_>>().join(","));
+            for snapshot in &snapshots {
+                let (main, values) = match &snapshot.report {
+                    Some(report) => (
+                        report.main().map(|it| it.to_string()).unwrap_or_default(),
+                        report_values(report, report_type)?,
+                    ),
+                    None => (String::new(), HashMap::new()),
+                };
+                let values = Case::iter().map(|case| values.get(&case).map_or_else(|| String::from("0"), |value| value.to_string())).collect::<Vec<_>>();
+                println!("{},{},{main},{}", snapshot.commit, history::date(snapshot.time), values.join(","));
+            }
+        },
+        Command::Explain { token } => {
+            println!("{}", Explanation::of(&token));
         },
         Command::Completions { shell } => {
             clap_complete::generate(shell, &mut <Args as CommandFactory>::command() , "casey", &mut std::io::stdout());
@@ -105,3 +578,203 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     Ok(())
 }
+
+/// Names the input in the locations of its tokens
+fn input_origin(file: &Option<String>, inline: &Option<String>) -> String {
+    match (file, inline) {
+        (Some(file_name), _) => file_name.clone(),
+        (None, Some(_)) => String::from("<inline>"),
+        (None, None) => String::from("<stdin>"),
+    }
+}
+
+/// The scanner for the kind of input selected by the arguments
+fn select_scanner(language: Option<Language>, kinds: &[syntax::IdentifierKind], format: Option<Format>, key_filter: &KeyFilter, dialect: &Option<Dialect>, columns: &[String], shell: bool) -> Scanner {
+    match (language, format, dialect) {
+        (Some(language), _, _) => Scanner::Syntax(language, kinds.to_vec()),
+        (None, Some(format), _) => Scanner::Keys(format, key_filter.clone()),
+        (None, None, Some(dialect)) => Scanner::Cells(dialect.clone(), columns.to_vec()),
+        (None, None, None) if shell => Scanner::Variables,
+        (None, None, None) => Scanner::Whitespace,
+    }
+}
+
+/// Returns the language to parse the input as, inferring it from the file extension
+/// when identifier kinds were requested without an explicit language
+fn resolve_language(language: Option<Language>, kinds: &[syntax::IdentifierKind], file: &Option<String>) -> Option<Language> {
+    if language.is_some() || kinds.is_empty() {
+        return language;
+    }
+
+    let inferred = file.as_ref().and_then(Language::from_path);
+    if inferred.is_none() {
+        Args::command().error(
+            clap::error::ErrorKind::MissingRequiredArgument,
+            "--kind requires a --language unless it can be inferred from the --file extension"
+        ).exit();
+    }
+    inferred
+}
+
+/// Returns the format to parse the input as, inferring it from the file extension
+/// when it isn't given, unless the input is parsed in another way and no option
+/// about keys was given
+fn resolve_format(format: Option<Format>, keys_requested: bool, parsed_otherwise: bool, file: &Option<String>) -> Option<Format> {
+    if format.is_some() || parsed_otherwise && !keys_requested {
+        return format;
+    }
+
+    let inferred = file.as_ref().and_then(Format::from_path);
+    if inferred.is_none() && keys_requested {
+        Args::command().error(
+            clap::error::ErrorKind::MissingRequiredArgument,
+            "--max-depth, --path and --group-by require a --format unless it can be inferred from the --file extension"
+        ).exit();
+    }
+    inferred
+}
+
+/// Returns how to split the input into the cells of a table, inferring the table format
+/// from the file extension when a delimiter or columns were given without an explicit one
+fn resolve_dialect(table: Option<TableFormat>, delimiter: Option<char>, columns: &[String], file: &Option<String>) -> Option<Dialect> {
+    let table = if table.is_some() || (delimiter.is_none() && columns.is_empty()) {
+        table
+    } else {
+        let inferred = file.as_ref().and_then(TableFormat::from_path);
+        if inferred.is_none() {
+            Args::command().error(
+                clap::error::ErrorKind::MissingRequiredArgument,
+                "--delimiter and --column require a --table unless it can be inferred from the --file extension"
+            ).exit();
+        }
+        inferred
+    };
+
+    let mut dialect = Dialect::from(table?);
+    if let Some(delimiter) = delimiter {
+        if !delimiter.is_ascii() {
+            Args::command().error(clap::error::ErrorKind::InvalidValue, "--delimiter must be an ASCII character").exit();
+        }
+        dialect.delimiter = delimiter as u8;
+    }
+    Some(dialect)
+}
+
+fn report_display(report: &FrequencyCaseReport, report_type: ReportType) -> Result<Box<dyn Display + '_>, CaseReportError> {
+    Ok(match report_type {
+        ReportType::Frequency => Box::new(report),
+        ReportType::Proportion => Box::new(report.proportions()),
+        ReportType::Percentage => Box::new(report.proportions().as_percentages()?),
+    })
+}
+
+/// The main case of `report` (or the reason there's none) along with all the cases
+/// tied for it and, if a report type is given, the full report
+fn case_summary(report: &FrequencyCaseReport, report_type: Option<ReportType>, main: Result<Case, CaseReportError>, confidence: Option<ConfidenceLevel>) -> Result<serde_json::Value, CaseReportError> {
+    let mut summary = json!({
+        "main": main.as_ref().ok().map(|case| case.to_string()),
+        "mixed": main.as_ref().is_err_and(CaseReportError::is_mixed),
+        "tied": report.tied_cases().iter().map(|case| case.to_string()).collect::<Vec<_>>(),
+    });
+
+    if let Some(report_type) = report_type {
+        let values = report_values(report, report_type)?;
+        summary["report"] = values.into_iter().map(|(case, value)| (case.to_string(), value)).collect::<serde_json::Map<_, _>>().into();
+    }
+
+    if let Some(level) = confidence {
+        let confidence = report.confidence(level, main.ok());
+        summary["confidence"] = json!({
+            "level": level.value(),
+            "intervals": confidence.intervals.iter()
+                                             .map(|(case, (low, high))| (case.to_string(), json!([low, high])))
+                                             .collect::<serde_json::Map<String, serde_json::Value>>(),
+            "dominance": confidence.dominance.map(|it| json!({
+                "main": it.main.to_string(),
+                "runner_up": it.runner_up.map(|case| case.to_string()),
+                "p_value": it.p_value,
+                "significant": it.is_significant(level),
+            })),
+        });
+    }
+
+    Ok(summary)
+}
+
+/// The use of each case in `report`, as numbers of the given report type
+fn report_values(report: &FrequencyCaseReport, report_type: ReportType) -> Result<HashMap<Case, serde_json::Value>, CaseReportError> {
+    Ok(match report_type {
+        ReportType::Frequency => report.frequencies.iter().map(|(case, value)| (*case, json!(value))).collect(),
+        ReportType::Proportion => report.proportions().frequencies.iter().map(|(case, value)| (*case, json_number(*value))).collect(),
+        ReportType::Percentage => report.proportions().as_percentages()?.percentages().iter().map(|(case, value)| (*case, json_number(*value))).collect(),
+    })
+}
+
+/// A JSON number written with the digits of the shortest representation of `value`,
+/// rather than of its widening to f64 (which turns 0.4 into 0.4000000059604645)
+fn json_number(value: f32) -> serde_json::Value {
+    value.to_string().parse::<f64>().map(|it| json!(it)).unwrap_or_default()
+}
+
+fn conversions(from: Vec<SourceCase>, to: Vec<Case>) -> Vec<(Case, Case)> {
+    from_to_cases(&from, &to).unwrap_or_else(|| {
+        Args::command().error(
+            clap::error::ErrorKind::TooFewValues,
+            "You must provide either one --to argument for each --from argument or a single --to argument"
+        ).exit()
+    })
+}
+
+/// Conversions from every other case to the main case of `sources`, if there's any
+fn main_case_conversions(sources: &[(&str, &Scanner)], tie_break: TieBreak, preferred: &[Case]) -> Result<Vec<(Case, Case)>, scan::ScanError> {
+    Ok(match report::main_case(sources, tie_break, preferred)? {
+        Some(main_case) => from_to_cases(&[SourceCase::Any], &[main_case]).unwrap_or_default(),
+        None => {
+            eprintln!("Not converting anything without a primarily used case (several cases may be used the most, see --tie-break).");
+            Vec::new()
+        },
+    })
+}
+
+/// Loads the bundled dictionary extended with the given vocabulary files,
+/// if any of them was requested
+fn load_dictionary(dictionary: bool, vocabulary: &[String]) -> io::Result<Option<Dictionary>> {
+    if !dictionary && vocabulary.is_empty() {
+        return Ok(None);
+    }
+
+    let mut dictionary = Dictionary::english();
+    for file_name in vocabulary {
+        dictionary.extend_from(&mut BufReader::new(File::open(file_name)?))?;
+    }
+    Ok(Some(dictionary))
+}
+
+/// Reports the collisions in `identifier_map` and handles them as requested,
+/// exiting if they must abort the conversion
+fn handle_collisions(identifier_map: &mut IdentifierMap, handling: CollisionHandling) {
+    if handling == CollisionHandling::Ignore {
+        return;
+    }
+
+    let collisions = identifier_map.collisions();
+    for collision in &collisions {
+        eprintln!("Collision: {}", collision);
+    }
+
+    match handling {
+        CollisionHandling::Abort if !collisions.is_empty() => {
+            eprintln!("Not converting anything because of collisions.");
+            std::process::exit(1);
+        },
+        CollisionHandling::Disambiguate => {
+            identifier_map.disambiguate();
+            for (name, _) in collisions.iter().flat_map(|it| &it.sources) {
+                if let Some(renamed) = identifier_map.renames.get(name) {
+                    eprintln!("Disambiguated: {name} -> {renamed}");
+                }
+            }
+        },
+        _ => {},
+    }
+}
@@ -0,0 +1,477 @@
+use crate::case::Case;
+use crate::detect::CaseDetect;
+use crate::convert::convert_token;
+use crate::dictionary::Dictionary;
+use crate::review::Change;
+use crate::scan::{LineIndex, Scanner, ScanError};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Formatter, Display, Result as FormatResult};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use itertools::Itertools;
+
+/// A mapping from old to new identifiers built over several inputs, so the
+/// same identifier gets renamed the same way everywhere
+#[derive(Debug, Default)]
+pub struct IdentifierMap {
+    pub renames: BTreeMap<String, String>,
+    unchanged: BTreeSet<String>,
+    locations: BTreeMap<String, Vec<Location>>,
+}
+
+/// Where a token appears in some input
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Location {
+    pub origin: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut Formatter) -> FormatResult {
+        write!(f, "{}:{}:{}", self.origin, self.line, self.column)
+    }
+}
+
+/// Several distinct identifiers that would end up being the same one,
+/// along with the locations of each of them
+#[derive(Debug, PartialEq, Eq)]
+pub struct Collision {
+    pub target: String,
+    pub sources: Vec<(String, Vec<Location>)>,
+}
+
+impl Display for Collision {
+    fn fmt(&self, f: &mut Formatter) -> FormatResult {
+        let names = self.sources.iter().map(|(name, _)| format!("'{name}'")).join(", ");
+        write!(f, "{names} would all become '{}'", self.target)?;
+        for (name, locations) in &self.sources {
+            write!(f, "\n  {name} at {}", locations.iter().join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl IdentifierMap {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Adds all tokens in `source` to the map. `origin` names the source
+    /// in the locations of its tokens
+    pub fn add_source(&mut self, origin: &str, source: &str, scanner: &Scanner, from_to_cases: &[(Case, Case)], dictionary: Option<&Dictionary>) -> Result<(), ScanError> {
+        let line_index = LineIndex::new(source);
+        for token in scanner.tokens(source)? {
+            let (line, column) = line_index.line_column(token.span.start);
+            self.locations.entry(String::from(token.text))
+                          .or_default()
+                          .push(Location { origin: String::from(origin), line, column });
+
+            match convert_token(token.text, from_to_cases, dictionary) {
+                Some(converted) if converted != token.text => {
+                    self.renames.insert(String::from(token.text), converted);
+                },
+                _ => {
+                    self.unchanged.insert(String::from(token.text));
+                },
+            }
+        }
+        Ok(())
+    }
+
+    /// Finds all targets that more than one distinct identifier would be
+    /// renamed to, including identifiers already using the target name
+    pub fn collisions(&self) -> Vec<Collision> {
+        self.renames.iter()
+                    .map(|(source, target)| (target, source))
+                    .into_group_map()
+                    .into_iter()
+                    .map(|(target, mut sources)| {
+                        if self.unchanged.contains(target) {
+                            sources.push(target);
+                        }
+                        Collision {
+                            target: target.clone(),
+                            sources: sources.into_iter()
+                                            .sorted()
+                                            .map(|name| (name.clone(), self.locations.get(name).cloned().unwrap_or_default()))
+                                            .collect(),
+                        }
+                    })
+                    .filter(|collision| collision.sources.len() > 1)
+                    .sorted_by(|x, y| x.target.cmp(&y.target))
+                    .collect()
+    }
+
+    /// Gives a distinct name to each identifier that collides with another one.
+    /// An identifier that already had the target name keeps it (otherwise, the
+    /// first one in alphabetical order does), and the rest get a numeric suffix
+    pub fn disambiguate(&mut self) {
+        for collision in self.collisions() {
+            let separator = separator_of(&collision.target);
+            let mut suffix = 2;
+            let renamed_sources = collision.sources.iter()
+                                                   .map(|(name, _)| name)
+                                                   .filter(|name| self.renames.contains_key(*name))
+                                                   .skip(if self.unchanged.contains(&collision.target) { 0 } else { 1 });
+
+            for source in renamed_sources.cloned().collect_vec() {
+                let new_name = loop {
+                    let candidate = format!("{}{separator}{suffix}", collision.target);
+                    suffix += 1;
+                    if !self.unchanged.contains(&candidate) && !self.renames.values().any(|it| *it == candidate) {
+                        break candidate;
+                    }
+                };
+                self.renames.insert(source, new_name);
+            }
+        }
+    }
+
+    /// Every token of `source` that would be renamed by the map
+    pub fn changes(&self, source: &str, scanner: &Scanner) -> Result<Vec<Change>, ScanError> {
+        Ok(scanner.tokens(source)?
+                  .into_iter()
+                  .filter_map(|token| self.renames.get(token.text).map(|renamed| Change {
+                      span: token.span,
+                      original: String::from(token.text),
+                      converted: renamed.clone(),
+                  }))
+                  .collect())
+    }
+
+    /// Renames every token of `source` that is in the map
+    pub fn apply(&self, source: &str, scanner: &Scanner) -> Result<String, ScanError> {
+        let mut result = String::with_capacity(source.len());
+        let mut last_end = 0;
+        for change in self.changes(source, scanner)? {
+            result.push_str(&source[last_end..change.span.start]);
+            result.push_str(&change.converted);
+            last_end = change.span.end;
+        }
+        result.push_str(&source[last_end..]);
+        Ok(result)
+    }
+}
+
+/// The separator to use before a numeric suffix so it reads as a new word in the case of `name`
+fn separator_of(name: &str) -> &'static str {
+    match Case::detect(name) {
+        Ok(Some(Case::KebabCase)) => "-",
+        Ok(Some(Case::CamelCase | Case::PascalCase)) => "",
+        _ if name.contains('-') => "-",
+        _ => "_",
+    }
+}
+
+/// Replaces the contents of every file, so either all of them are replaced or none
+/// is: new contents are first written to temporary files alongside the originals
+/// (with their permissions), and only once all of them are written are they renamed
+/// over the originals. Each original is kept alongside until every file is replaced,
+/// so they're all restored if any rename fails. Temporary files are never left behind,
+/// and existing files are never overwritten by them
+pub fn replace_all<P: AsRef<Path>>(files: &[(P, String)]) -> io::Result<()> {
+    replace_all_with(files, |from, to| fs::rename(from, to))
+}
+
+/// Like `replace_all`, replacing each original with its temporary file through `replace`
+fn replace_all_with<P: AsRef<Path>, F: Fn(&Path, &Path) -> io::Result<()>>(files: &[(P, String)], replace: F) -> io::Result<()> {
+    let sibling = |path: &Path, extension: &str| {
+        let file_name = path.file_name().map(|it| it.to_string_lossy()).unwrap_or_default();
+        path.with_file_name(format!(".{file_name}.{extension}"))
+    };
+    let remove_all = |temporary_files: &[PathBuf]| {
+        for temporary_file in temporary_files {
+            let _ = fs::remove_file(temporary_file);
+        }
+    };
+
+    let mut temporary_files: Vec<PathBuf> = Vec::new();
+    for (path, contents) in files {
+        let path = path.as_ref();
+        let temporary_path = sibling(path, "casey-tmp");
+
+        let written = OpenOptions::new().write(true).create_new(true).open(&temporary_path)
+            .and_then(|mut file| {
+                temporary_files.push(temporary_path.clone());
+                file.write_all(contents.as_bytes())
+            })
+            .and_then(|_| fs::metadata(path))
+            .and_then(|metadata| fs::set_permissions(&temporary_path, metadata.permissions()));
+        if let Err(err) = written {
+            remove_all(&temporary_files);
+            return Err(err);
+        }
+    }
+
+    let mut backups: Vec<PathBuf> = Vec::new();
+    for ((path, _), temporary_path) in files.iter().zip(&temporary_files) {
+        let path = path.as_ref();
+        let backup_path = sibling(path, "casey-orig");
+
+        let replaced = back_up(path, &backup_path).and_then(|_| {
+            backups.push(backup_path);
+            replace(temporary_path, path)
+        });
+        if let Err(err) = replaced {
+            for ((path, _), backup_path) in files.iter().zip(&backups) {
+                let _ = fs::rename(backup_path, path);
+            }
+            remove_all(&temporary_files);
+            remove_all(&backups);
+            return Err(err);
+        }
+    }
+    remove_all(&backups);
+
+    Ok(())
+}
+
+/// Keeps the contents of `path` at `backup_path`, which must not exist yet. A hard
+/// link keeps the original in place, but not every file system has them
+fn back_up(path: &Path, backup_path: &Path) -> io::Result<()> {
+    match fs::hard_link(path, backup_path) {
+        Err(err) if err.kind() != io::ErrorKind::AlreadyExists => {
+            let mut original = File::open(path)?;
+            let mut backup = OpenOptions::new().write(true).create_new(true).open(backup_path)?;
+            let copied = io::copy(&mut original, &mut backup)
+                .and_then(|_| backup.set_permissions(original.metadata()?.permissions()));
+            if copied.is_err() {
+                let _ = fs::remove_file(backup_path);
+            }
+            copied
+        },
+        result => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::syntax::Language;
+    use crate::structured::{Format, KeyFilter};
+    use indoc::indoc;
+
+    #[test]
+    fn builds_map_over_several_sources() -> Result<(), ScanError> {
+        // ARRANGE
+        let mut map = IdentifierMap::new();
+
+        // ACT
+        map.add_source("a", "userId = 1", &Scanner::Whitespace, &[(Case::CamelCase, Case::SnakeCase)], None)?;
+        map.add_source("b", "print userId userName", &Scanner::Whitespace, &[(Case::CamelCase, Case::SnakeCase)], None)?;
+
+        // ASSERT
+        assert_eq!(map.renames, BTreeMap::from([
+            (String::from("userId"), String::from("user_id")),
+            (String::from("userName"), String::from("user_name")),
+        ]));
+        Ok(())
+    }
+
+    #[test]
+    fn finds_collisions() -> Result<(), ScanError> {
+        // ARRANGE
+        let mut map = IdentifierMap::new();
+        let conversions = [(Case::CamelCase, Case::SnakeCase), (Case::KebabCase, Case::SnakeCase)];
+
+        // ACT
+        map.add_source("a", "fooBar foo-bar bazQux", &Scanner::Whitespace, &conversions, None)?;
+        map.add_source("b", "other\n  baz_qux", &Scanner::Whitespace, &conversions, None)?;
+
+        // ASSERT
+        let location = |origin: &str, line, column| vec![Location { origin: String::from(origin), line, column }];
+        assert_eq!(map.collisions(), vec![
+            Collision {
+                target: String::from("baz_qux"),
+                sources: vec![(String::from("bazQux"), location("a", 1, 16)), (String::from("baz_qux"), location("b", 2, 3))],
+            },
+            Collision {
+                target: String::from("foo_bar"),
+                sources: vec![(String::from("foo-bar"), location("a", 1, 8)), (String::from("fooBar"), location("a", 1, 1))],
+            },
+        ]);
+        Ok(())
+    }
+
+    #[test]
+    fn applies_map_to_source() -> Result<(), ScanError> {
+        // ARRANGE
+        let scanner = Scanner::Syntax(Language::Python, vec![]);
+        let mut map = IdentifierMap::new();
+        map.add_source("a.py", "def getUser(): pass", &scanner, &[(Case::CamelCase, Case::SnakeCase)], None)?;
+
+        // ACT
+        let result = map.apply("user = getUser()  # getUser", &scanner)?;
+
+        // ASSERT
+        assert_eq!(result, "user = get_user()  # getUser");
+        Ok(())
+    }
+
+    #[test]
+    fn applies_map_to_keys_only() -> Result<(), ScanError> {
+        // ARRANGE
+        let source = indoc! {r#"
+            {
+              "userId": "someValue",
+              "userData": [{"firstName": "lastName"}]
+            }
+        "#};
+        let scanner = Scanner::Keys(Format::Json, KeyFilter::default());
+        let mut map = IdentifierMap::new();
+        map.add_source("a.json", source, &scanner, &[(Case::CamelCase, Case::SnakeCase)], None)?;
+
+        // ACT
+        let result = map.apply(source, &scanner)?;
+
+        // ASSERT
+        assert_eq!(result, indoc! {r#"
+            {
+              "user_id": "someValue",
+              "user_data": [{"first_name": "lastName"}]
+            }
+        "#});
+        Ok(())
+    }
+
+    #[test]
+    fn collision_display() -> Result<(), ScanError> {
+        // ARRANGE
+        let mut map = IdentifierMap::new();
+        map.add_source("input", "fooBar\nfoo_bar fooBar", &Scanner::Whitespace, &[(Case::CamelCase, Case::SnakeCase)], None)?;
+
+        // ACT
+        let display = format!("{}", map.collisions()[0]);
+
+        // ASSERT
+        assert_eq!(display, indoc! {"
+            'fooBar', 'foo_bar' would all become 'foo_bar'
+              fooBar at input:1:1, input:2:9
+              foo_bar at input:2:1"
+        });
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn replaces_files_keeping_permissions() -> io::Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        // ARRANGE
+        let root = std::env::temp_dir().join(format!("casey-project-replace-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root)?;
+        let script = root.join("run.sh");
+        fs::write(&script, "echo $fooBar")?;
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755))?;
+
+        // ACT
+        let failed = replace_all(&[(script.clone(), String::from("echo $FOO")), (root.join("missing").join("a"), String::new())]);
+        replace_all(&[(script.clone(), String::from("echo $foo_bar"))])?;
+
+        // ASSERT
+        assert!(failed.is_err());
+        assert_eq!(fs::read_to_string(&script)?, "echo $foo_bar");
+        assert_eq!(fs::metadata(&script)?.permissions().mode() & 0o777, 0o755);
+        assert_eq!(fs::read_dir(&root)?.count(), 1);
+        fs::remove_dir_all(root)
+    }
+
+    #[test]
+    fn restores_files_when_one_cant_be_replaced() -> io::Result<()> {
+        // ARRANGE
+        let root = std::env::temp_dir().join(format!("casey-project-rollback-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("b"))?;
+        fs::write(root.join("a.txt"), "fooBar")?;
+        fs::write(root.join("c.txt"), "bazQux")?;
+
+        // ACT
+        // A directory can be written next to, but not replaced by a file
+        let result = replace_all(&[
+            (root.join("a.txt"), String::from("foo_bar")),
+            (root.join("b"), String::from("b")),
+            (root.join("c.txt"), String::from("baz_qux")),
+        ]);
+
+        // ASSERT
+        assert!(result.is_err());
+        assert_eq!(fs::read_to_string(root.join("a.txt"))?, "fooBar");
+        assert_eq!(fs::read_to_string(root.join("c.txt"))?, "bazQux");
+        assert!(root.join("b").is_dir());
+        assert_eq!(fs::read_dir(&root)?.count(), 3);
+        fs::remove_dir_all(root)
+    }
+
+    #[test]
+    fn restores_files_when_a_rename_fails() -> io::Result<()> {
+        // ARRANGE
+        let root = std::env::temp_dir().join(format!("casey-project-rename-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root)?;
+        let files: Vec<(PathBuf, String)> = ["a.txt", "b.txt", "c.txt"].iter()
+            .map(|name| (root.join(name), format!("{name} converted")))
+            .collect();
+        for (path, _) in &files {
+            fs::write(path, "fooBar")?;
+        }
+
+        // ACT
+        // The first two files are replaced before the last one fails
+        let result = replace_all_with(&files, |from, to| match to.ends_with("c.txt") {
+            true => Err(io::Error::other("rename failed")),
+            false => fs::rename(from, to),
+        });
+
+        // ASSERT
+        assert!(result.is_err());
+        for (path, _) in &files {
+            assert_eq!(fs::read_to_string(path)?, "fooBar");
+        }
+        assert_eq!(fs::read_dir(&root)?.count(), 3);
+        fs::remove_dir_all(root)
+    }
+
+    #[test]
+    fn keeps_existing_backup_files() -> io::Result<()> {
+        // ARRANGE
+        let root = std::env::temp_dir().join(format!("casey-project-backup-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root)?;
+        fs::write(root.join("a.txt"), "fooBar")?;
+        fs::write(root.join(".a.txt.casey-orig"), "someNotes")?;
+
+        // ACT
+        let result = replace_all(&[(root.join("a.txt"), String::from("foo_bar"))]);
+
+        // ASSERT
+        assert_eq!(result.map_err(|err| err.kind()), Err(io::ErrorKind::AlreadyExists));
+        assert_eq!(fs::read_to_string(root.join("a.txt"))?, "fooBar");
+        assert_eq!(fs::read_to_string(root.join(".a.txt.casey-orig"))?, "someNotes");
+        assert_eq!(fs::read_dir(&root)?.count(), 2);
+        fs::remove_dir_all(root)
+    }
+
+    #[test]
+    fn disambiguates_collisions() -> Result<(), ScanError> {
+        // ARRANGE
+        let mut map = IdentifierMap::new();
+        let conversions = [(Case::CamelCase, Case::SnakeCase), (Case::KebabCase, Case::SnakeCase)];
+        map.add_source("a", "fooBar foo-bar foo_bar_2 bazQux baz-qux", &Scanner::Whitespace, &conversions, None)?;
+
+        // ACT
+        map.disambiguate();
+
+        // ASSERT
+        assert!(map.collisions().is_empty());
+        assert_eq!(map.renames, BTreeMap::from([
+            (String::from("baz-qux"), String::from("baz_qux")),
+            (String::from("bazQux"), String::from("baz_qux_2")),
+            (String::from("foo-bar"), String::from("foo_bar")),
+            (String::from("fooBar"), String::from("foo_bar_3")),
+        ]));
+        Ok(())
+    }
+}
@@ -0,0 +1,299 @@
+use crate::case::Case;
+use crate::convert::convert_token;
+use crate::dictionary::Dictionary;
+use clap::ValueEnum;
+use itertools::Itertools;
+use std::collections::BTreeMap;
+use std::fmt::{Formatter, Display, Result as FormatResult};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
+pub enum RenameTarget {
+    #[value(help = "Rename only files")]
+    Files,
+    #[value(help = "Rename only directories")]
+    Directories,
+    #[value(help = "Rename both files and directories")]
+    All,
+}
+
+impl RenameTarget {
+    fn includes(&self, is_dir: bool) -> bool {
+        match self {
+            RenameTarget::Files => !is_dir,
+            RenameTarget::Directories => is_dir,
+            RenameTarget::All => true,
+        }
+    }
+}
+
+/// Renaming of a single file or directory, which stays in the same parent directory
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Rename {
+    pub from: PathBuf,
+    pub to: PathBuf,
+}
+
+/// Several entries of a directory whose names would only differ in case, or not at
+/// all, after renaming. They can't coexist on case-insensitive file systems
+#[derive(Debug, PartialEq, Eq)]
+pub struct RenameCollision {
+    pub directory: PathBuf,
+    pub target: String,
+    pub sources: Vec<String>,
+}
+
+impl Display for RenameCollision {
+    fn fmt(&self, f: &mut Formatter) -> FormatResult {
+        let names = self.sources.iter().map(|name| format!("'{name}'")).join(", ");
+        write!(f, "{names} would all become '{}' in {}", self.target, self.directory.display())
+    }
+}
+
+/// Converts the stem of a file or directory name, keeping everything from its first
+/// dot on (such as `.test.tsx`). Returns `None` if the name is left untouched
+pub fn convert_name(name: &str, from_to_cases: &[(Case, Case)], dictionary: Option<&Dictionary>) -> Option<String> {
+    let (stem, extension) = split_stem(name);
+    let converted = convert_token(stem, from_to_cases, dictionary)?;
+    (converted != stem).then(|| format!("{converted}{extension}"))
+}
+
+/// Splits a file or directory name into its stem and everything from its first dot on
+pub fn split_stem(name: &str) -> (&str, &str) {
+    // A leading dot marks a hidden file rather than an extension
+    let stem_end = name.char_indices()
+                       .skip(1)
+                       .find(|(_, c)| *c == '.')
+                       .map(|(index, _)| index)
+                       .unwrap_or(name.len());
+    name.split_at(stem_end)
+}
+
+/// Every file and directory under `root`, skipping hidden ones (such as `.git`)
+/// and everything inside them
+pub fn walk(root: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut entries = Vec::new();
+    let children = fs::read_dir(root)?
+                      .map(|entry| entry.map(|it| it.path()))
+                      .collect::<io::Result<Vec<PathBuf>>>()?;
+
+    for child in children.into_iter().sorted() {
+        if file_name(&child).is_some_and(|name| name.starts_with('.')) {
+            continue;
+        }
+        let is_dir = fs::symlink_metadata(&child)?.is_dir();
+        entries.push(child.clone());
+        if is_dir {
+            entries.extend(walk(&child)?);
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Finds the renames to make to `paths` and, if `recursive`, to everything under
+/// them. Entries come before the directories that contain them, so every rename
+/// can be made in order using the original path of its parent
+pub fn plan<P: AsRef<Path>>(paths: &[P], recursive: bool, target: RenameTarget, from_to_cases: &[(Case, Case)], dictionary: Option<&Dictionary>) -> io::Result<Vec<Rename>> {
+    let mut renames = Vec::new();
+    for path in paths {
+        plan_path(path.as_ref(), recursive, target, from_to_cases, dictionary, &mut renames)?;
+    }
+    Ok(renames)
+}
+
+fn plan_path(path: &Path, recursive: bool, target: RenameTarget, from_to_cases: &[(Case, Case)], dictionary: Option<&Dictionary>, renames: &mut Vec<Rename>) -> io::Result<()> {
+    let is_dir = fs::symlink_metadata(path)?.is_dir();
+
+    if is_dir && recursive {
+        let children = fs::read_dir(path)?
+                          .map(|entry| entry.map(|it| it.path()))
+                          .collect::<io::Result<Vec<PathBuf>>>()?;
+        for child in children.into_iter().sorted() {
+            plan_path(&child, recursive, target, from_to_cases, dictionary, renames)?;
+        }
+    }
+
+    if target.includes(is_dir) {
+        let new_name = path.file_name()
+                           .and_then(|name| name.to_str())
+                           .and_then(|name| convert_name(name, from_to_cases, dictionary));
+        if let Some(new_name) = new_name {
+            renames.push(Rename { from: path.to_path_buf(), to: path.with_file_name(new_name) });
+        }
+    }
+
+    Ok(())
+}
+
+/// Finds the entries that would end up with names that only differ in case,
+/// including entries that aren't renamed at all
+pub fn collisions(renames: &[Rename]) -> io::Result<Vec<RenameCollision>> {
+    let mut result = Vec::new();
+    let by_directory = renames.iter().into_group_map_by(|rename| rename.from.parent().map(Path::to_path_buf).unwrap_or_default());
+
+    for (directory, renames) in by_directory.into_iter().sorted_by(|x, y| x.0.cmp(&y.0)) {
+        let listed_directory = if directory.as_os_str().is_empty() { Path::new(".") } else { directory.as_path() };
+        let mut final_names: BTreeMap<String, String> = BTreeMap::new();
+        for entry in fs::read_dir(listed_directory)? {
+            if let Some(name) = entry?.file_name().to_str() {
+                final_names.insert(String::from(name), String::from(name));
+            }
+        }
+        for rename in &renames {
+            if let (Some(from), Some(to)) = (file_name(&rename.from), file_name(&rename.to)) {
+                final_names.insert(from, to);
+            }
+        }
+
+        let renamed: Vec<String> = renames.iter().filter_map(|rename| file_name(&rename.from)).collect();
+        let groups = final_names.into_iter()
+                                .map(|(source, target)| (target.to_lowercase(), (source, target)))
+                                .into_group_map();
+        for (_, group) in groups.into_iter().sorted() {
+            if group.len() > 1 && group.iter().any(|(source, _)| renamed.contains(source)) {
+                result.push(RenameCollision {
+                    directory: directory.clone(),
+                    target: group.iter().find(|(source, _)| renamed.contains(source)).map(|(_, target)| target.clone()).unwrap_or_default(),
+                    sources: group.into_iter().map(|(source, _)| source).sorted().collect(),
+                });
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+fn file_name(path: &Path) -> Option<String> {
+    path.file_name().and_then(|name| name.to_str()).map(String::from)
+}
+
+/// Makes the renames in order, through `git mv` if `git` is set. Renames that only
+/// change the case of a name go through a temporary name, so they also work on
+/// case-insensitive file systems
+pub fn apply(renames: &[Rename], git: bool) -> io::Result<()> {
+    let move_entry = |from: &Path, to: &Path| if git { git_mv(from, to) } else { fs::rename(from, to) };
+
+    for rename in renames {
+        let only_case_changes = file_name(&rename.from).map(|it| it.to_lowercase()) == file_name(&rename.to).map(|it| it.to_lowercase());
+        if only_case_changes {
+            let temporary_name = format!(".{}.casey-tmp", file_name(&rename.from).unwrap_or_default());
+            let temporary_path = rename.from.with_file_name(temporary_name);
+            move_entry(&rename.from, &temporary_path)?;
+            move_entry(&temporary_path, &rename.to)?;
+        } else {
+            move_entry(&rename.from, &rename.to)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn git_mv(from: &Path, to: &Path) -> io::Result<()> {
+    let output = Command::new("git").arg("mv").arg("--").arg(from).arg(to).output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "git mv {} {} failed: {}", from.display(), to.display(), String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A new empty directory only used by one test
+    fn test_directory(name: &str) -> io::Result<PathBuf> {
+        let path = std::env::temp_dir().join(format!("casey-rename-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path)?;
+        Ok(path)
+    }
+
+    #[test]
+    fn converts_stem_keeping_extensions() {
+        let conversions = [(Case::PascalCase, Case::KebabCase)];
+
+        assert_eq!(convert_name("MyComponent.test.tsx", &conversions, None), Some(String::from("my-component.test.tsx")));
+        assert_eq!(convert_name(".EslintRc", &conversions, None), None);
+        assert_eq!(convert_name("index.ts", &conversions, None), None);
+    }
+
+    #[test]
+    fn walks_tree_skipping_hidden_entries() -> io::Result<()> {
+        // ARRANGE
+        let root = test_directory("walk")?;
+        fs::create_dir_all(root.join(".git").join("objects"))?;
+        fs::create_dir_all(root.join("src"))?;
+        fs::write(root.join("src").join("main.rs"), "")?;
+        fs::write(root.join(".gitignore"), "")?;
+
+        // ACT
+        let entries = walk(&root)?;
+
+        // ASSERT
+        assert_eq!(entries, vec![root.join("src"), root.join("src").join("main.rs")]);
+        fs::remove_dir_all(root)
+    }
+
+    #[test]
+    fn plans_recursive_renames_children_first() -> io::Result<()> {
+        // ARRANGE
+        let root = test_directory("plan")?;
+        fs::create_dir_all(root.join("UserViews"))?;
+        fs::write(root.join("UserViews").join("LoginForm.tsx"), "")?;
+        fs::write(root.join("UserViews").join("index.ts"), "")?;
+
+        // ACT
+        let renames = plan(&[root.join("UserViews")], true, RenameTarget::All, &[(Case::PascalCase, Case::KebabCase)], None)?;
+
+        // ASSERT
+        assert_eq!(renames, vec![
+            Rename { from: root.join("UserViews").join("LoginForm.tsx"), to: root.join("UserViews").join("login-form.tsx") },
+            Rename { from: root.join("UserViews"), to: root.join("user-views") },
+        ]);
+        fs::remove_dir_all(root)
+    }
+
+    #[test]
+    fn finds_case_insensitive_collisions() -> io::Result<()> {
+        // ARRANGE
+        let root = test_directory("collisions")?;
+        fs::write(root.join("UserList.ts"), "")?;
+        fs::write(root.join("USER_LIST.ts"), "")?;
+        fs::write(root.join("Other.ts"), "")?;
+        let conversions = [(Case::PascalCase, Case::SnakeCase), (Case::ShoutingSnakeCase, Case::SnakeCase)];
+
+        // ACT
+        let renames = plan(&[root.join("UserList.ts"), root.join("USER_LIST.ts")], false, RenameTarget::Files, &conversions, None)?;
+        let collisions = collisions(&renames)?;
+
+        // ASSERT
+        assert_eq!(collisions, vec![RenameCollision {
+            directory: root.clone(),
+            target: String::from("user_list.ts"),
+            sources: vec![String::from("USER_LIST.ts"), String::from("UserList.ts")],
+        }]);
+        fs::remove_dir_all(root)
+    }
+
+    #[test]
+    fn applies_case_only_renames() -> io::Result<()> {
+        // ARRANGE
+        let root = test_directory("apply")?;
+        fs::write(root.join("Readme"), "contents")?;
+        let renames = plan(&[root.join("Readme")], false, RenameTarget::Files, &[(Case::PascalCase, Case::ShoutingSnakeCase)], None)?;
+
+        // ACT
+        apply(&renames, false)?;
+
+        // ASSERT
+        let names: Vec<String> = fs::read_dir(&root)?.filter_map(|entry| entry.ok()?.file_name().into_string().ok()).collect();
+        assert_eq!(names, vec![String::from("README")]);
+        fs::remove_dir_all(root)
+    }
+}
@@ -1,19 +1,51 @@
 use crate::case::Case;
 use std::{collections::HashMap, cmp::Ordering};
 use std::error::Error;
-use std::io::BufRead;
+use std::io::{self, BufRead};
 use crate::detect::CaseDetect;
+use crate::syntax::{self, Language, IdentifierKind};
+use crate::structured::{self, Format, Key, KeyFilter, PathSegment, StructuredError};
+use crate::table::{self, Dialect};
+use crate::scan::{LineIndex, Scanner, ScanError};
+use crate::project::Location;
+use crate::shell;
+use crate::stats::{self, ConfidenceLevel};
+use crate::rename;
+use std::path::{Path, PathBuf};
+use clap::ValueEnum;
+use std::collections::{BTreeMap, HashSet};
 use num_traits::Num;
 use thiserror::Error as ThisError;
 use std::fmt::{Formatter, Display, Result as FormatResult};
 use itertools::Itertools;
 
+#[allow(clippy::enum_variant_names)]
 #[derive(ThisError, PartialEq, Debug)]
 pub enum CaseReportError {
     #[error("source report frequency '{0}' is not in the 0..1 range, so it can't be converted to percentages")]
     PercentageConversionError(f32),
     #[error("there's more than one primarily used case: {0:?}")]
     ManyMainCasesError(Vec<Case>),
+    #[error("the most frequent case, {0}, is only used by {:.1}% of the tokens", .1 * 100_f32)]
+    MixedCasesError(Case, f32),
+    #[error("there are only {0} tokens, fewer than the required {1}")]
+    TooFewTokensError(u32, u32),
+}
+
+impl CaseReportError {
+    /// Whether the error means the input doesn't clearly use a single case
+    pub fn is_mixed(&self) -> bool {
+        matches!(self, CaseReportError::MixedCasesError(..) | CaseReportError::TooFewTokensError(..))
+    }
+}
+
+/// How clearly the main case has to stand out to be reported
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Threshold {
+    /// Share of the tokens, between 0 and 1, that have to use the main case
+    pub min_share: f32,
+    /// Number of tokens with a case there have to be in total
+    pub min_tokens: u32,
 }
 
 #[derive(Debug)]
@@ -21,13 +53,28 @@ pub struct CaseReport<T> {
     pub frequencies: HashMap<Case, T>,
 }
 
+/// How to pick the main case when several cases are used the most. By default,
+/// none is picked, so every command agrees there's no main case on a tie
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug, Default)]
+pub enum TieBreak {
+    #[value(help = "Pick the tied case that appears first in the input")]
+    FirstOccurrence,
+    #[value(help = "Pick the tied case that comes first in the --prefer list")]
+    Preferred,
+    #[value(help = "Pick the tied case whose name comes first alphabetically")]
+    Alphabetical,
+    #[value(help = "Don't pick any case")]
+    #[default]
+    Fail,
+}
+
 pub type FrequencyCaseReport = CaseReport<u32>;
 type ProportionCaseReport = CaseReport<f32>;
 
 impl <T: Num + Display + PartialOrd> Display for CaseReport<T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> FormatResult {
         let result = self.frequencies.iter()
-                                     .sorted_by(|x, y| x.1.partial_cmp(&y.1).unwrap())
+                                     .sorted_by(|x, y| x.1.partial_cmp(y.1).unwrap())
                                      .rev()
                                      .map(|(case, freq)| format!("{case}: {freq}"))
                                      .join("\n")
@@ -56,6 +103,98 @@ impl FrequencyCaseReport {
 
     }
 
+    /// Builds a report only from the identifiers of the given kinds (or all
+    /// of them if `kinds` is empty) in source code of the given language
+    pub fn from_syntax<T: BufRead>(input: &mut T, language: Language, kinds: &[IdentifierKind]) -> Result<Option<Self>, Box<dyn Error>> {
+        let mut source = String::new();
+        input.read_to_string(&mut source)?;
+
+        let mut frequencies: HashMap<Case, u32> = HashMap::new();
+        for identifier in syntax::identifiers(&source, language) {
+            if !kinds.is_empty() && !kinds.contains(&identifier.kind) {
+                continue;
+            }
+            // Quoted identifiers may contain whitespace, and then they have no case
+            if let Ok(Some(case)) = Case::detect(identifier.name) {
+                *frequencies.entry(case).or_insert(0) += 1;
+            }
+        }
+
+        if !frequencies.is_empty() {
+            Ok(Some(CaseReport { frequencies }))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Builds a report only from the keys of the objects in a structured document
+    /// that pass the filter, ignoring their values
+    pub fn from_keys<T: BufRead>(input: &mut T, format: Format, filter: &KeyFilter) -> Result<Option<Self>, Box<dyn Error>> {
+        let mut source = String::new();
+        input.read_to_string(&mut source)?;
+
+        let keys = distinct_keys(&source, format, filter)?;
+        Ok(Self::from_names(keys.iter().map(|key| key.name.as_str())))
+    }
+
+    /// Builds a report only from the header cells of a table, or if some
+    /// columns are given, from the cells of those columns in the other rows
+    pub fn from_table<T: BufRead>(input: &mut T, dialect: &Dialect, columns: &[String]) -> Result<Option<Self>, Box<dyn Error>> {
+        let mut source = String::new();
+        input.read_to_string(&mut source)?;
+
+        let cells = table::selected_cells(&source, dialect, columns)?;
+        Ok(Self::from_names(cells.iter().map(|cell| cell.text)))
+    }
+
+    /// Builds a report only from the names of the variables assigned or
+    /// referenced in a shell script or .env file
+    pub fn from_variables<T: BufRead>(input: &mut T) -> Result<Option<Self>, Box<dyn Error>> {
+        let mut source = String::new();
+        input.read_to_string(&mut source)?;
+
+        Ok(Self::from_names(shell::variables(&source).iter().map(|variable| variable.name)))
+    }
+
+    /// Builds a report from the stems of the names of all files and directories
+    /// under `root`, leaving out extensions
+    pub fn from_filenames(root: &Path) -> Result<Option<Self>, Box<dyn Error>> {
+        let names = filename_stems(root)?;
+        Ok(Self::from_names(names.iter().map(String::as_str)))
+    }
+
+    /// Adds up several reports, as if they were built from all their inputs together
+    pub fn combine<'a>(reports: impl IntoIterator<Item = &'a FrequencyCaseReport>) -> Option<Self> {
+        let mut frequencies: HashMap<Case, u32> = HashMap::new();
+        for report in reports {
+            for (case, frequency) in &report.frequencies {
+                *frequencies.entry(*case).or_insert(0) += frequency;
+            }
+        }
+
+        if !frequencies.is_empty() {
+            Some(CaseReport { frequencies })
+        } else {
+            None
+        }
+    }
+
+    fn from_names<'a>(names: impl Iterator<Item = &'a str>) -> Option<Self> {
+        let mut frequencies: HashMap<Case, u32> = HashMap::new();
+        for name in names {
+            // Keys may contain whitespace, and then they have no case
+            if let Ok(Some(case)) = Case::detect(name) {
+                *frequencies.entry(case).or_insert(0) += 1;
+            }
+        }
+
+        if !frequencies.is_empty() {
+            Some(CaseReport { frequencies })
+        } else {
+            None
+        }
+    }
+
     pub fn proportions(&self) -> ProportionCaseReport {
        let total_frequencies: u32 = self.frequencies.values().sum();
 
@@ -66,32 +205,92 @@ impl FrequencyCaseReport {
                                         .collect()
        }
     }
+
+    /// The confidence intervals of the shares of the cases and, if there's a main
+    /// case, the test of whether it's used more than the next most frequent one
+    pub fn confidence(&self, level: ConfidenceLevel, main: Option<Case>) -> ConfidenceReport {
+        let total: u32 = self.frequencies.values().sum();
+        let by_frequency = self.frequencies.iter()
+                                           .sorted_by(|x, y| y.1.cmp(x.1).then(x.0.cmp(y.0)))
+                                           .collect_vec();
+
+        let dominance = main.map(|main| {
+            let runner_up = by_frequency.iter().find(|(case, _)| **case != main).map(|(case, freq)| (**case, **freq));
+            DominanceTest {
+                main,
+                runner_up: runner_up.map(|it| it.0),
+                p_value: stats::sign_test(self.frequencies.get(&main).copied().unwrap_or(0), runner_up.map_or(0, |it| it.1)),
+            }
+        });
+
+        ConfidenceReport {
+            level,
+            intervals: by_frequency.into_iter()
+                                   .map(|(case, freq)| (*case, stats::wilson_interval(*freq, total, level)))
+                                   .collect(),
+            dominance,
+        }
+    }
+
+    /// The main case, as long as there are enough tokens and it's used by a large
+    /// enough share of them. Otherwise, the input is considered to mix cases, even
+    /// if several cases are tied for the main one
+    pub fn dominant(&self, tie_break: TieBreak, preferred: &[Case], threshold: Threshold) -> Result<Case, CaseReportError> {
+        let tokens: u32 = self.frequencies.values().sum();
+        if tokens < threshold.min_tokens {
+            return Err(CaseReportError::TooFewTokensError(tokens, threshold.min_tokens));
+        }
+
+        let tied_cases = self.tied_cases();
+        let share = self.proportions().frequencies[&tied_cases[0]];
+        if share < threshold.min_share {
+            let main_case = self.main_with(tie_break, preferred).unwrap_or(tied_cases[0]);
+            return Err(CaseReportError::MixedCasesError(main_case, share));
+        }
+
+        self.main_with(tie_break, preferred)
+    }
 }
 
 impl <T: Num + Ord + PartialEq + Copy> CaseReport<T> {
     pub fn main(&self) -> Result<Case, CaseReportError> {
-            // No problem calling unwrap...
-            // the report can't be created if there are
-            // 0 instances
-            let main_case = *self.frequencies.iter()
-                            .max_by(|x, y| x.1.cmp(y.1))
-                            .map(|it| it.0)
-                            .unwrap();
-            let main_case_occurrences = self.frequencies[&main_case];
-            let main_cases = self.frequencies
-                .iter()
-                .map(|(case, freq)| (*case, *freq))
-                .filter(|(_, freq)| freq.partial_cmp(&main_case_occurrences).unwrap_or(Ordering::Equal).is_eq())
-                .map(|it| it.0)
-                .sorted()
-                .collect_vec()
-                ;
-
-            if main_cases.len() == 1 {
-                Ok(main_case)
-            } else {
-                Err(CaseReportError::ManyMainCasesError(main_cases))
-            }
+        self.main_with(TieBreak::default(), &[])
+    }
+
+    /// The main case, picking one of the tied cases with the given strategy if
+    /// several cases are used the most. `preferred` lists the cases to pick first,
+    /// which for `TieBreak::FirstOccurrence` is the order they appear in the input
+    pub fn main_with(&self, tie_break: TieBreak, preferred: &[Case]) -> Result<Case, CaseReportError> {
+        let main_cases = self.tied_cases();
+        if let [main_case] = main_cases[..] {
+            return Ok(main_case);
+        }
+
+        let picked = match tie_break {
+            TieBreak::FirstOccurrence | TieBreak::Preferred => preferred.iter().find(|case| main_cases.contains(case)).copied(),
+            TieBreak::Alphabetical => main_cases.iter().min_by_key(|case| case.to_string().to_lowercase()).copied(),
+            TieBreak::Fail => None,
+        };
+        picked.ok_or(CaseReportError::ManyMainCasesError(main_cases))
+    }
+
+    /// All the cases used the most, which is more than one on a tie
+    pub fn tied_cases(&self) -> Vec<Case> {
+        // No problem calling unwrap...
+        // the report can't be created if there are
+        // 0 instances
+        let main_case = *self.frequencies.iter()
+                        .max_by(|x, y| x.1.cmp(y.1))
+                        .map(|it| it.0)
+                        .unwrap();
+        let main_case_occurrences = self.frequencies[&main_case];
+        self.frequencies
+            .iter()
+            .map(|(case, freq)| (*case, *freq))
+            .filter(|(_, freq)| freq.partial_cmp(&main_case_occurrences).unwrap_or(Ordering::Equal).is_eq())
+            .map(|it| it.0)
+            .sorted()
+            .collect_vec()
     }
 }
 
@@ -101,7 +300,7 @@ pub struct PercentageCaseReport(ProportionCaseReport);
 impl Display for PercentageCaseReport {
     fn fmt(&self, f: &mut Formatter<'_>) -> FormatResult {
         let result = self.0.frequencies.iter()
-                                     .sorted_by(|x, y| x.1.partial_cmp(&y.1).unwrap())
+                                     .sorted_by(|x, y| x.1.partial_cmp(y.1).unwrap())
                                      .rev()
                                      .map(|(case, freq)| format!("{case}: {freq}%"))
                                      .join("\n")
@@ -111,6 +310,12 @@ impl Display for PercentageCaseReport {
     }
 }
 
+impl PercentageCaseReport {
+    pub fn percentages(&self) -> &HashMap<Case, f32> {
+        &self.0.frequencies
+    }
+}
+
 impl ProportionCaseReport {
     pub fn as_percentages(&self) -> Result<PercentageCaseReport, CaseReportError> {
         for proportion in self.frequencies.values() {
@@ -131,6 +336,237 @@ impl ProportionCaseReport {
     }
 }
 
+/// The case most used by the tokens of all `sources`, or `None` if none of them
+/// has a case. A tie is settled with the given strategy
+pub fn main_case(sources: &[(&str, &Scanner)], tie_break: TieBreak, preferred: &[Case]) -> Result<Option<Case>, ScanError> {
+    let mut tokens = Vec::new();
+    for (source, scanner) in sources {
+        tokens.extend(scanner.tokens(source)?.into_iter().map(|token| token.text));
+    }
+
+    let preferred = match tie_break {
+        TieBreak::FirstOccurrence => cases_in_order(tokens.iter().copied()),
+        _ => preferred.to_vec(),
+    };
+    Ok(FrequencyCaseReport::from_names(tokens.into_iter()).and_then(|report| report.main_with(tie_break, &preferred).ok()))
+}
+
+/// The cases used by the tokens of all `sources`, in the order they first appear,
+/// for breaking ties by first occurrence
+pub fn first_seen(sources: &[(&str, &Scanner)]) -> Result<Vec<Case>, ScanError> {
+    let mut tokens = Vec::new();
+    for (source, scanner) in sources {
+        tokens.extend(scanner.tokens(source)?.into_iter().map(|token| token.text));
+    }
+    Ok(cases_in_order(tokens.into_iter()))
+}
+
+/// The cases used by the stems of the names of all files and directories under
+/// `root`, in the order they're found
+pub fn first_seen_in_filenames(root: &Path) -> io::Result<Vec<Case>> {
+    let names = filename_stems(root)?;
+    Ok(cases_in_order(names.iter().map(String::as_str)))
+}
+
+fn cases_in_order<'a>(names: impl Iterator<Item = &'a str>) -> Vec<Case> {
+    let mut cases = Vec::new();
+    for name in names {
+        if let Ok(Some(case)) = Case::detect(name) {
+            if !cases.contains(&case) {
+                cases.push(case);
+            }
+        }
+    }
+    cases
+}
+
+fn filename_stems(root: &Path) -> io::Result<Vec<String>> {
+    Ok(rename::walk(root)?.iter()
+                          .filter_map(|path| path.file_name()?.to_str())
+                          .map(|name| String::from(rename::split_stem(name).0))
+                          .collect())
+}
+
+/// The result of a sign test of the tokens using the main case against the tokens
+/// using the next most frequent case (or no tokens if there's no other case)
+#[derive(Debug, PartialEq)]
+pub struct DominanceTest {
+    pub main: Case,
+    pub runner_up: Option<Case>,
+    pub p_value: f64,
+}
+
+impl DominanceTest {
+    pub fn is_significant(&self, level: ConfidenceLevel) -> bool {
+        self.p_value < 1.0 - level.value()
+    }
+}
+
+/// How precisely a report estimates the share of each case, most used cases first
+#[derive(Debug, PartialEq)]
+pub struct ConfidenceReport {
+    pub level: ConfidenceLevel,
+    pub intervals: Vec<(Case, (f64, f64))>,
+    pub dominance: Option<DominanceTest>,
+}
+
+impl Display for ConfidenceReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FormatResult {
+        write!(f, "{} confidence intervals:", self.level)?;
+        for (case, (low, high)) in &self.intervals {
+            write!(f, "\n{case}: {:.1}%-{:.1}%", low * 100.0, high * 100.0)?;
+        }
+
+        if let Some(dominance) = &self.dominance {
+            let verb = if dominance.is_significant(self.level) { "is" } else { "isn't" };
+            let other = dominance.runner_up.map_or(String::from("any other case"), |it| it.to_string());
+            let p_value = if dominance.p_value < 0.0001 { String::from("p < 0.0001") } else { format!("p = {:.4}", dominance.p_value) };
+            write!(f, "\n{} {verb} used significantly more than {other} ({p_value})", dominance.main)?;
+        }
+        Ok(())
+    }
+}
+
+/// Every distinct token of `source` that doesn't use the expected case, along with
+/// its locations, in the order they first appear. Like conversions, only tokens whose
+/// case is detected are considered, so those without a case (such as `=`, `42` or
+/// `foo_bar,`) are left out
+pub fn non_conforming_tokens(origin: &str, source: &str, scanner: &Scanner, expected: Case) -> Result<Vec<(String, Vec<Location>)>, ScanError> {
+    let mut tokens: Vec<(String, Vec<Location>)> = Vec::new();
+    let mut positions: HashMap<&str, usize> = HashMap::new();
+    let line_index = LineIndex::new(source);
+    for token in scanner.tokens(source)? {
+        if !matches!(Case::detect(token.text), Ok(Some(case)) if case != expected) {
+            continue;
+        }
+
+        let (line, column) = line_index.line_column(token.span.start);
+        let location = Location { origin: String::from(origin), line, column };
+        match positions.get(token.text) {
+            Some(position) => tokens[*position].1.push(location),
+            None => {
+                positions.insert(token.text, tokens.len());
+                tokens.push((String::from(token.text), vec![location]));
+            },
+        }
+    }
+    Ok(tokens)
+}
+
+/// Every file and directory under `root` whose stem uses a case other than the
+/// expected one. Like in the report, stems without a case (such as `v2`) are left out
+pub fn filename_outliers(root: &Path, expected: Case) -> io::Result<Vec<PathBuf>> {
+    Ok(rename::walk(root)?.into_iter()
+                          .filter(|path| {
+                              let name = path.file_name().and_then(|it| it.to_str()).unwrap_or_default();
+                              matches!(Case::detect(rename::split_stem(name).0), Ok(Some(case)) if case != expected)
+                          })
+                          .collect())
+}
+
+/// The keys of a structured document that pass the filter. Keys that appear several
+/// times at the same path (such as in repeated TOML table headers) are only kept once
+fn distinct_keys(source: &str, format: Format, filter: &KeyFilter) -> Result<Vec<Key>, StructuredError> {
+    let mut seen_paths = HashSet::new();
+    Ok(structured::keys(source, format)?
+                  .into_iter()
+                  .filter(|key| filter.matches(key) && seen_paths.insert(key.path.clone()))
+                  .collect())
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
+pub enum KeyGrouping {
+    #[value(help = "Group keys by how many objects deep they are")]
+    Depth,
+    #[value(help = "Group keys by the path of their object, treating all elements of an array alike")]
+    Path,
+}
+
+/// The cases of the keys of a structured document grouped by nesting level or by
+/// the path of their objects, along with the objects whose keys mix several cases
+#[derive(Debug)]
+pub struct KeyCaseReport {
+    pub groups: Vec<(String, FrequencyCaseReport)>,
+    pub mixed_objects: Vec<MixedObject>,
+}
+
+/// An object whose keys use more than one case, along with the keys in each case
+#[derive(Debug, PartialEq, Eq)]
+pub struct MixedObject {
+    pub path: String,
+    pub keys: BTreeMap<Case, Vec<String>>,
+}
+
+impl Display for MixedObject {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FormatResult {
+        let cases = self.keys.iter()
+                             .map(|(case, names)| format!("{case} ({})", names.join(", ")))
+                             .join(", ");
+        write!(f, "{} mixes {cases}", self.path)
+    }
+}
+
+impl KeyCaseReport {
+    pub fn from_keys(source: &str, format: Format, filter: &KeyFilter, grouping: KeyGrouping) -> Result<Self, StructuredError> {
+        let mut keys = distinct_keys(source, format, filter)?;
+
+        let mut mixed_objects = Vec::new();
+        for (path, object_keys) in group_in_order(&keys, |key| structured::display_path(key.object_path())) {
+            let mut keys_by_case: BTreeMap<Case, Vec<String>> = BTreeMap::new();
+            for key in object_keys {
+                if let Ok(Some(case)) = Case::detect(&key.name) {
+                    keys_by_case.entry(case).or_default().push(key.name.clone());
+                }
+            }
+            if keys_by_case.len() > 1 {
+                mixed_objects.push(MixedObject { path, keys: keys_by_case });
+            }
+        }
+
+        let groups = match grouping {
+            KeyGrouping::Depth => {
+                keys.sort_by_key(|key| key.depth());
+                group_in_order(&keys, |key| format!("depth {}", key.depth()))
+            },
+            KeyGrouping::Path => group_in_order(&keys, |key| object_pattern(key.object_path())),
+        };
+
+        Ok(KeyCaseReport {
+            groups: groups.into_iter()
+                          .filter_map(|(group, keys)| {
+                              FrequencyCaseReport::from_names(keys.iter().map(|key| key.name.as_str()))
+                                                  .map(|report| (group, report))
+                          })
+                          .collect(),
+            mixed_objects,
+        })
+    }
+}
+
+/// Groups `keys` by the name `group_of` gives them, in the order each group first appears
+fn group_in_order<F: Fn(&Key) -> String>(keys: &[Key], group_of: F) -> Vec<(String, Vec<&Key>)> {
+    let mut groups: Vec<(String, Vec<&Key>)> = Vec::new();
+    for key in keys {
+        let group = group_of(key);
+        match groups.iter_mut().find(|(name, _)| *name == group) {
+            Some((_, group_keys)) => group_keys.push(key),
+            None => groups.push((group, vec![key])),
+        }
+    }
+    groups
+}
+
+/// Formats an object path like `$.items[*].data`, so all elements of an array share it
+fn object_pattern(path: &[PathSegment]) -> String {
+    let path = path.iter()
+                   .map(|segment| match segment {
+                       PathSegment::Key(name) => format!(".{name}"),
+                       PathSegment::Index(_) => String::from("[*]"),
+                   })
+                   .join("");
+    format!("${path}")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -198,6 +634,321 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn finds_cases_of_identifier_kind() -> Result<(), Box<dyn Error>> {
+        // ARRANGE
+        let mut reader = BufReader::new(indoc! {r#"
+            class UserAccount:
+                def get_name(self, userId):
+                    return fetchName(userId)
+        "#}.as_bytes());
+
+        // ACT
+        let report = CaseReport::from_syntax(&mut reader, Language::Python, &[IdentifierKind::Function])?.unwrap();
+
+        // ASSERT
+        assert_eq!(report.frequencies.len(), 2);
+        assert_eq!(report.frequencies[&Case::SnakeCase], 1);
+        assert_eq!(report.frequencies[&Case::CamelCase], 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn skips_quoted_identifiers_with_whitespace() -> Result<(), Box<dyn Error>> {
+        // ARRANGE
+        let source = r#"SELECT "first name", userId FROM users;"#;
+
+        // ACT
+        let report = CaseReport::from_syntax(&mut source.as_bytes(), Language::Sql, &[])?.unwrap();
+
+        // ASSERT
+        assert_eq!(report.frequencies, HashMap::from([(Case::CamelCase, 1)]));
+        assert_eq!(main_case(&[(source, &Scanner::Syntax(Language::Sql, vec![]))], TieBreak::Fail, &[])?, Some(Case::CamelCase));
+
+        Ok(())
+    }
+
+    #[test]
+    fn finds_cases_of_keys() -> Result<(), Box<dyn Error>> {
+        // ARRANGE
+        let mut reader = BufReader::new(indoc! {r#"
+            [serverConfig]
+            hostName = "some_value"
+
+            [serverConfig]
+            portNumber = 80
+        "#}.as_bytes());
+
+        // ACT
+        let report = CaseReport::from_keys(&mut reader, Format::Toml, &KeyFilter::default())?.unwrap();
+
+        // ASSERT
+        assert_eq!(report.frequencies.len(), 1);
+        assert_eq!(report.frequencies[&Case::CamelCase], 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn finds_cases_of_table_header() -> Result<(), Box<dyn Error>> {
+        // ARRANGE
+        let mut reader = BufReader::new(indoc! {r#"
+            user_id,"user_name",lastLogin,"Some Notes"
+            fooBar,bazQux,never,whatEver
+        "#}.as_bytes());
+
+        // ACT
+        let report = CaseReport::from_table(&mut reader, &Dialect::from(table::TableFormat::Csv), &[])?.unwrap();
+
+        // ASSERT
+        assert_eq!(report.frequencies.len(), 2);
+        assert_eq!(report.frequencies[&Case::SnakeCase], 2);
+        assert_eq!(report.frequencies[&Case::CamelCase], 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn finds_cases_of_variables() -> Result<(), Box<dyn Error>> {
+        // ARRANGE
+        let mut reader = BufReader::new(indoc! {r#"
+            DB_HOST=someHost
+            export dbPort=5432
+            API_URL="http://$DB_HOST:$dbPort"
+        "#}.as_bytes());
+
+        // ACT
+        let report = CaseReport::from_variables(&mut reader)?.unwrap();
+
+        // ASSERT
+        assert_eq!(report.frequencies[&Case::ShoutingSnakeCase], 3);
+        assert_eq!(report.frequencies[&Case::CamelCase], 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn finds_main_case_of_several_sources() -> Result<(), Box<dyn Error>> {
+        // ARRANGE
+        let python = Scanner::Syntax(Language::Python, vec![]);
+        let first = "def get_user(userId): pass";
+        let second = "user_name = userName + otherName";
+
+        // ACT
+        let overall = main_case(&[(first, &python), (second, &python)], TieBreak::Fail, &[])?;
+        let tied = main_case(&[(first, &python)], TieBreak::FirstOccurrence, &[])?;
+
+        // ASSERT
+        assert_eq!(overall, Some(Case::CamelCase));
+        assert_eq!(tied, Some(Case::SnakeCase));
+        assert_eq!(main_case(&[(first, &python)], TieBreak::Fail, &[])?, None);
+        assert_eq!(main_case(&[("1 + 2", &Scanner::Whitespace)], TieBreak::FirstOccurrence, &[])?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn lists_non_conforming_tokens() -> Result<(), Box<dyn Error>> {
+        // ARRANGE
+        let source = indoc! {r#"
+            DB_HOST=someHost
+            export dbPort=5432
+            API_URL="http://$DB_HOST:$dbPort"
+        "#};
+
+        // ACT
+        let tokens = non_conforming_tokens(".env", source, &Scanner::Variables, Case::ShoutingSnakeCase)?;
+
+        // ASSERT
+        let location = |line, column| Location { origin: String::from(".env"), line, column };
+        assert_eq!(tokens, vec![(String::from("dbPort"), vec![location(2, 8), location(3, 27)])]);
+        assert_eq!(non_conforming_tokens("input", "x = 42 userName", &Scanner::Whitespace, Case::SnakeCase)?.len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn finds_cases_of_filenames() -> Result<(), Box<dyn Error>> {
+        // ARRANGE
+        let root = std::env::temp_dir().join(format!("casey-report-filenames-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("user-views"))?;
+        std::fs::write(root.join("user-views").join("login-form.test.tsx"), "")?;
+        std::fs::write(root.join("user-views").join("SignupForm.tsx"), "")?;
+        std::fs::write(root.join("user-views").join("index.ts"), "")?;
+
+        // ACT
+        let report = CaseReport::from_filenames(&root)?.unwrap();
+        let outliers = filename_outliers(&root, report.main()?)?;
+
+        // ASSERT
+        assert_eq!(report.frequencies[&Case::KebabCase], 2);
+        assert_eq!(report.frequencies[&Case::PascalCase], 1);
+        assert_eq!(outliers, vec![root.join("user-views").join("SignupForm.tsx")]);
+
+        std::fs::remove_dir_all(root)?;
+        Ok(())
+    }
+
+    #[test]
+    fn filenames_without_a_case_are_not_outliers() -> Result<(), Box<dyn Error>> {
+        // ARRANGE
+        let root = std::env::temp_dir().join(format!("casey-report-digit-filenames-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root)?;
+        for name in ["utf8_decoder.rs", "v2.rs", "user_account.rs", "loginForm.rs"] {
+            std::fs::write(root.join(name), "")?;
+        }
+
+        // ACT
+        let outliers = filename_outliers(&root, Case::SnakeCase)?;
+
+        // ASSERT
+        assert_eq!(outliers, vec![root.join("loginForm.rs")]);
+
+        std::fs::remove_dir_all(root)?;
+        Ok(())
+    }
+
+    #[test]
+    fn groups_key_cases() -> Result<(), Box<dyn Error>> {
+        // ARRANGE
+        let source = r#"{"userId": 1, "items": [{"item_name": "valueOne"}, {"item_name": 2, "itemPrice": 3}]}"#;
+
+        // ACT
+        let by_depth = KeyCaseReport::from_keys(source, Format::Json, &KeyFilter::default(), KeyGrouping::Depth)?;
+        let by_path = KeyCaseReport::from_keys(source, Format::Json, &KeyFilter::default(), KeyGrouping::Path)?;
+
+        // ASSERT
+        let groups = |report: &KeyCaseReport| report.groups.iter()
+                                                           .map(|(group, report)| (group.clone(), report.frequencies.clone()))
+                                                           .collect_vec();
+        assert_eq!(groups(&by_depth), vec![
+            (String::from("depth 1"), HashMap::from([(Case::CamelCase, 1)])),
+            (String::from("depth 2"), HashMap::from([(Case::SnakeCase, 2), (Case::CamelCase, 1)])),
+        ]);
+        assert_eq!(groups(&by_path), vec![
+            (String::from("$"), HashMap::from([(Case::CamelCase, 1)])),
+            (String::from("$.items[*]"), HashMap::from([(Case::SnakeCase, 2), (Case::CamelCase, 1)])),
+        ]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn flags_objects_mixing_cases() -> Result<(), Box<dyn Error>> {
+        // ARRANGE
+        let source = indoc! {"
+            user_id: 1
+            items:
+              - item_name: someValue
+                itemPrice: 3
+                itemCount: 3
+              - item_name: 2
+        "};
+
+        // ACT
+        let report = KeyCaseReport::from_keys(source, Format::Yaml, &KeyFilter::default(), KeyGrouping::Depth)?;
+
+        // ASSERT
+        assert_eq!(report.mixed_objects.len(), 1);
+        assert_eq!(format!("{}", report.mixed_objects[0]), "$.items[0] mixes camelCase (itemPrice, itemCount), snake_case (item_name)");
+
+        Ok(())
+    }
+
+    #[test]
+    fn breaks_ties() -> Result<(), Box<dyn Error>> {
+        // ARRANGE
+        let source = "snake_case camelCase PascalCase otherCase other_case";
+
+        // ACT
+        let report = CaseReport::from(&mut source.as_bytes())?.unwrap();
+        let order = first_seen(&[(source, &Scanner::Whitespace)])?;
+
+        // ASSERT
+        assert_eq!(report.tied_cases(), vec![Case::CamelCase, Case::SnakeCase]);
+        assert_eq!(order, vec![Case::SnakeCase, Case::CamelCase, Case::PascalCase]);
+        assert_eq!(report.main_with(TieBreak::FirstOccurrence, &order), Ok(Case::SnakeCase));
+        assert_eq!(report.main_with(TieBreak::Preferred, &[Case::PascalCase, Case::CamelCase]), Ok(Case::CamelCase));
+        assert_eq!(report.main_with(TieBreak::Alphabetical, &[]), Ok(Case::CamelCase));
+        assert_eq!(report.main_with(TieBreak::Preferred, &[Case::KebabCase]), Err(CaseReportError::ManyMainCasesError(vec![Case::CamelCase, Case::SnakeCase])));
+        assert_eq!(report.main_with(TieBreak::Fail, &[]), Err(CaseReportError::ManyMainCasesError(vec![Case::CamelCase, Case::SnakeCase])));
+        Ok(())
+    }
+
+    #[test]
+    fn requires_dominant_case_to_clear_threshold() -> Result<(), Box<dyn Error>> {
+        // ARRANGE
+        let mut reader = BufReader::new("camelCase otherCase snake_case PascalCase kebab-case".as_bytes());
+
+        // ACT
+        let report = CaseReport::from(&mut reader)?.unwrap();
+
+        // ASSERT
+        assert_eq!(report.dominant(TieBreak::Fail, &[], Threshold::default()), Ok(Case::CamelCase));
+        assert_eq!(report.dominant(TieBreak::Fail, &[], Threshold { min_share: 0.4, min_tokens: 5 }), Ok(Case::CamelCase));
+        assert_eq!(report.dominant(TieBreak::Fail, &[], Threshold { min_share: 0.6, min_tokens: 0 }), Err(CaseReportError::MixedCasesError(Case::CamelCase, 0.4)));
+        assert_eq!(report.dominant(TieBreak::Fail, &[], Threshold { min_share: 0.0, min_tokens: 10 }), Err(CaseReportError::TooFewTokensError(5, 10)));
+        assert_eq!(CaseReportError::MixedCasesError(Case::CamelCase, 0.4).to_string(), "the most frequent case, camelCase, is only used by 40.0% of the tokens");
+        Ok(())
+    }
+
+    #[test]
+    fn tie_below_threshold_is_mixed() -> Result<(), Box<dyn Error>> {
+        // ARRANGE
+        let mut reader = BufReader::new("aB e_f".as_bytes());
+
+        // ACT
+        let report = CaseReport::from(&mut reader)?.unwrap();
+
+        // ASSERT
+        assert_eq!(report.dominant(TieBreak::Fail, &[], Threshold { min_share: 0.9, min_tokens: 0 }), Err(CaseReportError::MixedCasesError(Case::CamelCase, 0.5)));
+        assert_eq!(report.dominant(TieBreak::Fail, &[], Threshold { min_share: 0.0, min_tokens: 3 }), Err(CaseReportError::TooFewTokensError(2, 3)));
+        assert_eq!(report.dominant(TieBreak::Fail, &[], Threshold::default()), Err(CaseReportError::ManyMainCasesError(vec![Case::CamelCase, Case::SnakeCase])));
+        Ok(())
+    }
+
+    #[test]
+    fn tests_dominance_of_main_case() -> Result<(), Box<dyn Error>> {
+        // ARRANGE
+        let tokens = "camelCase ".repeat(9) + "snake_case PascalCase";
+        let report = CaseReport::from(&mut tokens.as_bytes())?.unwrap();
+
+        // ACT
+        let confidence = report.confidence(ConfidenceLevel::NinetyFive, Some(Case::CamelCase));
+
+        // ASSERT
+        assert_eq!(confidence.intervals.iter().map(|it| it.0).collect_vec(), vec![Case::CamelCase, Case::PascalCase, Case::SnakeCase]);
+        let dominance = confidence.dominance.as_ref().unwrap();
+        assert_eq!(dominance.runner_up, Some(Case::PascalCase));
+        assert!(dominance.is_significant(ConfidenceLevel::NinetyFive));
+        assert!(!dominance.is_significant(ConfidenceLevel::NinetyNine));
+        assert_eq!(confidence.to_string(), indoc! {"
+            95% confidence intervals:
+            camelCase: 52.3%-94.9%
+            PascalCase: 1.6%-37.7%
+            snake_case: 1.6%-37.7%
+            camelCase is used significantly more than PascalCase (p = 0.0107)"
+        });
+        Ok(())
+    }
+
+    #[test]
+    fn combines_reports() -> Result<(), Box<dyn Error>> {
+        // ARRANGE
+        let first = CaseReport::from(&mut "user_id userName".as_bytes())?.unwrap();
+        let second = CaseReport::from(&mut "UserList user_name".as_bytes())?.unwrap();
+
+        // ACT
+        let combined = FrequencyCaseReport::combine([&first, &second]).unwrap();
+
+        // ASSERT
+        assert_eq!(combined.frequencies, HashMap::from([(Case::SnakeCase, 2), (Case::CamelCase, 1), (Case::PascalCase, 1)]));
+        assert!(FrequencyCaseReport::combine([]).is_none());
+        Ok(())
+    }
+
     #[test]
     fn correct_proportions() -> Result<(), Box<dyn Error>> {
         // ARRANGE
@@ -0,0 +1,188 @@
+use crate::scan::LineIndex;
+use std::collections::HashSet;
+use std::io::{self, BufRead, Write};
+use std::ops::Range;
+
+/// A replacement of some token of an input
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Change {
+    pub span: Range<usize>,
+    pub original: String,
+    pub converted: String,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Decision {
+    Accept,
+    Reject,
+    Edit(String),
+    AcceptAll,
+    Quit,
+}
+
+/// Asks whether to apply each change to `source`, showing it in context through
+/// `prompt` and reading the answers from `answers`. Returns `source` with only
+/// the accepted changes applied
+pub fn review<R: BufRead, W: Write>(origin: &str, source: &str, changes: &[Change], answers: &mut R, prompt: &mut W) -> io::Result<String> {
+    let mut result = String::with_capacity(source.len());
+    let mut last_end = 0;
+    let mut accepted_tokens: HashSet<&str> = HashSet::new();
+    let mut quit = false;
+    let line_index = LineIndex::new(source);
+
+    for change in changes {
+        let replacement = if quit {
+            None
+        } else if accepted_tokens.contains(change.original.as_str()) {
+            Some(change.converted.clone())
+        } else {
+            show_change(origin, source, &line_index, change, prompt)?;
+            match ask(answers, prompt)? {
+                Decision::Accept => Some(change.converted.clone()),
+                Decision::Reject => None,
+                Decision::Edit(text) => Some(text),
+                Decision::AcceptAll => {
+                    accepted_tokens.insert(&change.original);
+                    Some(change.converted.clone())
+                },
+                Decision::Quit => {
+                    quit = true;
+                    None
+                },
+            }
+        };
+
+        if let Some(replacement) = replacement {
+            result.push_str(&source[last_end..change.span.start]);
+            result.push_str(&replacement);
+            last_end = change.span.end;
+        }
+    }
+    result.push_str(&source[last_end..]);
+
+    Ok(result)
+}
+
+fn show_change<W: Write>(origin: &str, source: &str, line_index: &LineIndex, change: &Change, prompt: &mut W) -> io::Result<()> {
+    let (line, column) = line_index.line_column(change.span.start);
+    let line_start = line_index.line_start(line - 1).unwrap_or(0);
+    let line_end = source[change.span.start..].find('\n').map(|it| change.span.start + it).unwrap_or(source.len());
+
+    writeln!(prompt, "{origin}:{line}:{column}")?;
+    writeln!(prompt, "  {}", &source[line_start..line_end])?;
+    writeln!(prompt, "  {}{}", " ".repeat(column - 1), "^".repeat(change.original.chars().count()))?;
+    writeln!(prompt, "{} -> {}", change.original, change.converted)?;
+    Ok(())
+}
+
+fn ask<R: BufRead, W: Write>(answers: &mut R, prompt: &mut W) -> io::Result<Decision> {
+    loop {
+        write!(prompt, "Convert? [y]es, [n]o, [e]dit, [a]ll of this token, [q]uit: ")?;
+        prompt.flush()?;
+
+        let mut answer = String::new();
+        if answers.read_line(&mut answer)? == 0 {
+            return Ok(Decision::Quit);
+        }
+
+        match answer.trim() {
+            "y" | "yes" => return Ok(Decision::Accept),
+            "n" | "no" => return Ok(Decision::Reject),
+            "a" | "all" => return Ok(Decision::AcceptAll),
+            "q" | "quit" => return Ok(Decision::Quit),
+            "e" | "edit" => {
+                write!(prompt, "Replace with: ")?;
+                prompt.flush()?;
+                let mut text = String::new();
+                answers.read_line(&mut text)?;
+                // Replacing a token with nothing would delete it, so that rejects the change instead
+                return Ok(match text.trim() {
+                    "" => Decision::Reject,
+                    text => Decision::Edit(String::from(text)),
+                });
+            },
+            _ => writeln!(prompt, "Unknown answer '{}'", answer.trim())?,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    fn changes() -> Vec<Change> {
+        [(0..6, "fooBar", "foo_bar"), (7..13, "bazQux", "baz_qux"), (14..20, "fooBar", "foo_bar"), (21..27, "bazQux", "baz_qux")]
+            .into_iter()
+            .map(|(span, original, converted)| Change { span, original: String::from(original), converted: String::from(converted) })
+            .collect()
+    }
+
+    #[test]
+    fn applies_only_accepted_changes() -> io::Result<()> {
+        // ARRANGE
+        let source = "fooBar bazQux fooBar bazQux";
+        let mut answers = "n\na\ne\nfoo_baz\n".as_bytes();
+        let mut prompt = vec![];
+
+        // ACT
+        let result = review("input", source, &changes(), &mut answers, &mut prompt)?;
+
+        // ASSERT
+        assert_eq!(result, "fooBar baz_qux foo_baz baz_qux");
+        Ok(())
+    }
+
+    #[test]
+    fn quitting_rejects_remaining_changes() -> io::Result<()> {
+        // ARRANGE
+        let source = "fooBar bazQux fooBar bazQux";
+        let mut answers = "y\nq\n".as_bytes();
+        let mut prompt = vec![];
+
+        // ACT
+        let result = review("input", source, &changes(), &mut answers, &mut prompt)?;
+
+        // ASSERT
+        assert_eq!(result, "foo_bar bazQux fooBar bazQux");
+        Ok(())
+    }
+
+    #[test]
+    fn empty_edit_rejects_change() -> io::Result<()> {
+        // ARRANGE
+        let source = "fooBar bazQux fooBar bazQux";
+        let mut answers = "e\n\ny\ny\ne\n".as_bytes();
+        let mut prompt = vec![];
+
+        // ACT
+        let result = review("input", source, &changes(), &mut answers, &mut prompt)?;
+
+        // ASSERT
+        assert_eq!(result, "fooBar baz_qux foo_bar bazQux");
+        Ok(())
+    }
+
+    #[test]
+    fn shows_changes_in_context() -> io::Result<()> {
+        // ARRANGE
+        let source = "first line\nlet fooBar = 1;";
+        let changes = [Change { span: 15..21, original: String::from("fooBar"), converted: String::from("foo_bar") }];
+        let mut answers = "maybe\ny\n".as_bytes();
+        let mut prompt = vec![];
+
+        // ACT
+        review("input", source, &changes, &mut answers, &mut prompt)?;
+
+        // ASSERT
+        assert_eq!(String::from_utf8(prompt).unwrap(), indoc! {"
+            input:2:5
+              let fooBar = 1;
+                  ^^^^^^
+            fooBar -> foo_bar
+            Convert? [y]es, [n]o, [e]dit, [a]ll of this token, [q]uit: Unknown answer 'maybe'
+            Convert? [y]es, [n]o, [e]dit, [a]ll of this token, [q]uit: "
+        });
+        Ok(())
+    }
+}
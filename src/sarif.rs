@@ -0,0 +1,204 @@
+use crate::case::Case;
+use crate::convert::ConvertCaseTo;
+use crate::detect::CaseDetect;
+use crate::lint::Violation;
+use serde_json::{json, Value};
+use std::path::{Component, Path};
+
+/// Base of the URIs of relative paths, which is the directory given as the source root
+const SOURCE_ROOT: &str = "%SRCROOT%";
+
+/// A SARIF 2.1.0 log of the violations, for code scanning tools. Each expected case
+/// is a rule, and each violation a result suggesting to convert its token. Relative
+/// paths are resolved against `root`
+pub fn log(violations: &[Violation], root: &Path) -> Value {
+    let mut cases: Vec<Case> = violations.iter().map(|it| it.expected).collect();
+    cases.sort();
+    cases.dedup();
+
+    let rules: Vec<Value> = cases.iter()
+        .map(|case| json!({
+            "id": case.to_string(),
+            "shortDescription": { "text": format!("Tokens use {case}") },
+            "defaultConfiguration": { "level": "warning" },
+        }))
+        .collect();
+
+    let results: Vec<Value> = violations.iter()
+        .map(|violation| {
+            let rule_index = cases.iter().position(|it| *it == violation.expected);
+            let mut result = json!({
+                "ruleId": violation.expected.to_string(),
+                "ruleIndex": rule_index,
+                "level": "warning",
+                "message": { "text": format!("'{}' isn't {}", violation.token, violation.expected) },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": artifact_location(&violation.location.origin),
+                        "region": region(violation),
+                    },
+                }],
+            });
+
+            // Only a token that's a single identifier with a case can be replaced without touching its surroundings
+            let converted = violation.token.convert_case_to(violation.expected);
+            if matches!(Case::detect(&violation.token), Ok(Some(_))) && converted != violation.token {
+                result["fixes"] = json!([{
+                    "description": { "text": format!("Convert to {}", violation.expected) },
+                    "artifactChanges": [{
+                        "artifactLocation": artifact_location(&violation.location.origin),
+                        "replacements": [{
+                            "deletedRegion": region(violation),
+                            "insertedContent": { "text": converted },
+                        }],
+                    }],
+                }]);
+            }
+            result
+        })
+        .collect();
+
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                },
+            },
+            "originalUriBaseIds": {
+                SOURCE_ROOT: { "uri": format!("{}/", file_uri(root).trim_end_matches('/')) },
+            },
+            // Token columns count characters, not UTF-16 code units as SARIF assumes by default
+            "columnKind": "unicodeCodePoints",
+            "results": results,
+        }],
+    })
+}
+
+/// Where the file a violation is in is, as a URI relative to the source root, or
+/// an absolute one for files outside of it. Inputs that aren't files (such as
+/// `<stdin>`) have no URI and are only described
+fn artifact_location(origin: &str) -> Value {
+    let path = Path::new(origin);
+    if origin.starts_with('<') {
+        json!({ "description": { "text": origin } })
+    } else if path.is_absolute() {
+        json!({ "uri": file_uri(path) })
+    } else {
+        json!({ "uri": encode_path(path), "uriBaseId": SOURCE_ROOT })
+    }
+}
+
+fn file_uri(path: &Path) -> String {
+    format!("file:///{}", encode_path(path))
+}
+
+/// The components of a path joined by slashes, percent-encoding every byte of
+/// their names that isn't an unreserved character of a URI
+fn encode_path(path: &Path) -> String {
+    let encode = |name: &str| name.bytes()
+                                  .map(|byte| match byte {
+                                      b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => char::from(byte).to_string(),
+                                      byte => format!("%{byte:02X}"),
+                                  })
+                                  .collect::<String>();
+
+    path.components()
+        .filter_map(|component| match component {
+            Component::Normal(name) => Some(encode(&name.to_string_lossy())),
+            Component::ParentDir => Some(String::from("..")),
+            // Drive letters, such as `C:`, are kept as they are in file URIs
+            Component::Prefix(prefix) => Some(prefix.as_os_str().to_string_lossy().into_owned()),
+            Component::RootDir | Component::CurDir => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn region(violation: &Violation) -> Value {
+    json!({
+        "startLine": violation.location.line,
+        "startColumn": violation.location.column,
+        "endColumn": violation.location.column + violation.token.chars().count(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lint;
+    use crate::project::Location;
+    use crate::scan::{Scanner, ScanError};
+    use crate::syntax::Language;
+
+    #[test]
+    fn logs_violations_with_fixes() -> Result<(), ScanError> {
+        // ARRANGE
+        let scanner = Scanner::Syntax(Language::Python, vec![]);
+        let violations = lint::violations("src/a.py", "user_id = 1\nuserName = user_id\n", &scanner, Case::SnakeCase, None)?;
+
+        // ACT
+        let log = log(&violations, Path::new("/repo"));
+
+        // ASSERT
+        let run = &log["runs"][0];
+        assert_eq!(log["version"], "2.1.0");
+        assert_eq!(run["tool"]["driver"]["rules"][0]["id"], "snake_case");
+        assert_eq!(run["originalUriBaseIds"]["%SRCROOT%"]["uri"], "file:///repo/");
+        assert_eq!(run["results"], json!([{
+            "ruleId": "snake_case",
+            "ruleIndex": 0,
+            "level": "warning",
+            "message": { "text": "'userName' isn't snake_case" },
+            "locations": [{
+                "physicalLocation": {
+                    "artifactLocation": { "uri": "src/a.py", "uriBaseId": "%SRCROOT%" },
+                    "region": { "startLine": 2, "startColumn": 1, "endColumn": 9 },
+                },
+            }],
+            "fixes": [{
+                "description": { "text": "Convert to snake_case" },
+                "artifactChanges": [{
+                    "artifactLocation": { "uri": "src/a.py", "uriBaseId": "%SRCROOT%" },
+                    "replacements": [{
+                        "deletedRegion": { "startLine": 2, "startColumn": 1, "endColumn": 9 },
+                        "insertedContent": { "text": "user_name" },
+                    }],
+                }],
+            }],
+        }]));
+        Ok(())
+    }
+
+    #[test]
+    fn only_fixes_identifiers() -> Result<(), ScanError> {
+        // ARRANGE
+        let violations = lint::violations("notes.txt", "call(fooBar) and foo_bar, baz_qux.\n", &Scanner::Whitespace, Case::SnakeCase, None)?;
+        let punctuated = Violation {
+            token: String::from("call(fooBar)"),
+            location: Location { origin: String::from("notes.txt"), line: 1, column: 1 },
+            expected: Case::SnakeCase,
+            context: String::from("call(fooBar) and foo_bar, baz_qux."),
+        };
+
+        // ACT
+        let log = log(&[punctuated], Path::new("/repo"));
+
+        // ASSERT
+        assert!(violations.is_empty());
+        assert_eq!(log["runs"][0]["results"][0]["message"]["text"], "'call(fooBar)' isn't snake_case");
+        assert!(log["runs"][0]["results"][0].get("fixes").is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn encodes_artifact_locations() {
+        assert_eq!(artifact_location("src/my file#1.py"), json!({ "uri": "src/my%20file%231.py", "uriBaseId": "%SRCROOT%" }));
+        assert_eq!(artifact_location("/tmp/größe.py"), json!({ "uri": "file:///tmp/gr%C3%B6%C3%9Fe.py" }));
+        assert_eq!(artifact_location("<stdin>"), json!({ "description": { "text": "<stdin>" } }));
+    }
+}
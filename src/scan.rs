@@ -0,0 +1,174 @@
+use crate::syntax::{self, Language, IdentifierKind};
+use crate::structured::{self, Format, KeyFilter, StructuredError};
+use crate::table::{self, Dialect, TableError};
+use crate::shell;
+use std::ops::Range;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ScanError {
+    #[error(transparent)]
+    StructuredError(#[from] StructuredError),
+    #[error(transparent)]
+    TableError(#[from] TableError),
+}
+
+/// A token of some input along with its byte span in it
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Token<'a> {
+    pub text: &'a str,
+    pub span: Range<usize>,
+}
+
+/// How to split an input into the tokens whose case is detected or converted
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Scanner {
+    /// Every whitespace-separated word is a token
+    Whitespace,
+    /// Only identifiers of the given kinds (or all of them if empty) are tokens
+    Syntax(Language, Vec<IdentifierKind>),
+    /// Only the keys of objects in a structured document that pass the filter are tokens
+    Keys(Format, KeyFilter),
+    /// Only the header cells of a table are tokens, or if some columns are given,
+    /// the cells of those columns in all the other rows
+    Cells(Dialect, Vec<String>),
+    /// Only the names of the variables assigned or referenced in a shell script or .env file are tokens
+    Variables,
+}
+
+impl Scanner {
+    pub fn tokens<'a>(&self, source: &'a str) -> Result<Vec<Token<'a>>, ScanError> {
+        Ok(match self {
+            Scanner::Whitespace => source.split_whitespace()
+                                         .map(|text| {
+                                             let start = text.as_ptr() as usize - source.as_ptr() as usize;
+                                             Token { text, span: start..start + text.len() }
+                                         })
+                                         .collect(),
+            Scanner::Syntax(language, kinds) => syntax::identifiers(source, *language)
+                                                  .into_iter()
+                                                  .filter(|it| kinds.is_empty() || kinds.contains(&it.kind))
+                                                  .map(|it| Token { text: it.name, span: it.span })
+                                                  .collect(),
+            Scanner::Keys(format, filter) => structured::keys(source, *format)?
+                                                .into_iter()
+                                                .filter(|key| filter.matches(key))
+                                                .map(|key| Token { text: &source[key.span.clone()], span: key.span })
+                                                .collect(),
+            Scanner::Cells(dialect, columns) => table::selected_cells(source, dialect, columns)?
+                                                      .into_iter()
+                                                      .map(|cell| Token { text: cell.text, span: cell.span })
+                                                      .collect(),
+            Scanner::Variables => shell::variables(source)
+                                        .into_iter()
+                                        .map(|variable| Token { text: variable.name, span: variable.span })
+                                        .collect(),
+        })
+    }
+}
+
+/// 1-based line and column of a byte offset in `source`. To locate many offsets
+/// of the same source, build a [`LineIndex`] once instead
+pub fn line_column(source: &str, offset: usize) -> (usize, usize) {
+    LineIndex::new(source).line_column(offset)
+}
+
+/// The byte offsets where each line of a source starts, so offsets in it can be
+/// located without rescanning everything before them
+#[derive(Debug, Clone)]
+pub struct LineIndex<'a> {
+    source: &'a str,
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub fn new(source: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+        LineIndex { source, line_starts }
+    }
+
+    /// 1-based line and column of a byte offset in the source
+    pub fn line_column(&self, offset: usize) -> (usize, usize) {
+        let line = self.line_starts.partition_point(|start| *start <= offset);
+        let line_start = self.line_starts[line - 1];
+        (line, self.source[line_start..offset].chars().count() + 1)
+    }
+
+    /// Byte offset where the given 0-based line starts, if the source has it
+    pub fn line_start(&self, line: usize) -> Option<usize> {
+        self.line_starts.get(line).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn whitespace_token_spans() -> Result<(), ScanError> {
+        let source = "  fooBar\n baz_qux";
+
+        let tokens = Scanner::Whitespace.tokens(source)?;
+
+        assert_eq!(tokens, vec![
+            Token { text: "fooBar", span: 2..8 },
+            Token { text: "baz_qux", span: 10..17 },
+        ]);
+        Ok(())
+    }
+
+    #[test]
+    fn syntax_tokens_filter_kinds() -> Result<(), ScanError> {
+        let source = "fn fooBar(bazQux: u32) {}";
+
+        let tokens = Scanner::Syntax(Language::Rust, vec![IdentifierKind::Variable]).tokens(source)?;
+
+        assert_eq!(tokens, vec![Token { text: "bazQux", span: 10..16 }]);
+        Ok(())
+    }
+
+    #[test]
+    fn key_tokens_filter_depth() -> Result<(), ScanError> {
+        let source = r#"{"userId": "fooBar", "userData": {"firstName": 1}}"#;
+
+        let tokens = Scanner::Keys(Format::Json, KeyFilter { max_depth: Some(1), paths: vec![] }).tokens(source)?;
+
+        assert_eq!(tokens, vec![Token { text: "userId", span: 2..8 }, Token { text: "userData", span: 22..30 }]);
+        Ok(())
+    }
+
+    #[test]
+    fn cell_tokens_of_header() -> Result<(), ScanError> {
+        let source = "userId,\"userName\"\nfooBar,bazQux\n";
+
+        let tokens = Scanner::Cells(Dialect::from(table::TableFormat::Csv), vec![]).tokens(source)?;
+
+        assert_eq!(tokens, vec![Token { text: "userId", span: 0..6 }, Token { text: "userName", span: 8..16 }]);
+        Ok(())
+    }
+
+    #[test]
+    fn line_column_of_offset() {
+        let source = "first line\nsecond line";
+
+        assert_eq!(line_column(source, 0), (1, 1));
+        assert_eq!(line_column(source, 18), (2, 8));
+    }
+
+    #[test]
+    fn line_index_locates_offsets_on_every_line() {
+        let source = "first\n\nthird líne\nlast";
+        let index = LineIndex::new(source);
+
+        assert_eq!(index.line_column(0), (1, 1));
+        assert_eq!(index.line_column(5), (1, 6));
+        assert_eq!(index.line_column(6), (2, 1));
+        assert_eq!(index.line_column(13), (3, 7));
+        assert_eq!(index.line_column(19), (4, 1));
+        assert_eq!(index.line_column(source.len()), (4, 5));
+        assert_eq!(index.line_start(2), Some(7));
+        assert_eq!(index.line_start(4), None);
+    }
+}
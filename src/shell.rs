@@ -0,0 +1,402 @@
+use std::ops::Range;
+
+/// Whether a variable is being given a value or having its value used
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Usage {
+    Definition,
+    Reference,
+}
+
+/// A variable name in a shell script or .env file
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Variable<'a> {
+    pub name: &'a str,
+    pub span: Range<usize>,
+    pub usage: Usage,
+}
+
+/// Commands whose arguments are the names of the variables they act on
+const DECLARATION_COMMANDS: &[&str] = &["export", "readonly", "local", "declare", "typeset", "unset", "read"];
+
+/// Reserved words after which a new command starts
+const COMMAND_PREFIXES: &[&str] = &["if", "then", "else", "elif", "do", "while", "until", "!", "time", "{"];
+
+/// Finds the names of the variables assigned (`NAME=value`, `export NAME`, `for NAME in`...)
+/// and referenced (`$NAME`, `${NAME:-default}`, `$((NAME + 1))`) in a shell script or .env
+/// file. Values, commands, single-quoted strings and comments are left out
+pub fn variables(source: &str) -> Vec<Variable<'_>> {
+    let mut scanner = ShellScanner { source, bytes: source.as_bytes(), variables: Vec::new(), heredocs: Vec::new() };
+    scanner.script();
+    // Names in an assignment's value are found before the name being assigned
+    scanner.variables.sort_by_key(|variable| variable.span.start);
+    scanner.variables
+}
+
+struct Heredoc {
+    delimiter: String,
+    expands: bool,
+    strips_tabs: bool,
+}
+
+struct ShellScanner<'a> {
+    source: &'a str,
+    bytes: &'a [u8],
+    variables: Vec<Variable<'a>>,
+    heredocs: Vec<Heredoc>,
+}
+
+impl <'a> ShellScanner<'a> {
+    fn script(&mut self) {
+        let mut pos = 0;
+        let mut command_start = true;
+        let mut declared_usage: Option<Usage> = None;
+        let mut loop_variable = false;
+
+        while pos < self.bytes.len() {
+            match self.bytes[pos] {
+                b' ' | b'\t' => pos += 1,
+                b'\\' if self.bytes.get(pos + 1) == Some(&b'\n') => pos += 2,
+                b'\n' => {
+                    pos = self.heredoc_bodies(pos + 1);
+                    command_start = true;
+                    declared_usage = None;
+                },
+                b'#' => pos = self.find_byte(pos, b'\n'),
+                b'(' if self.bytes.get(pos + 1) == Some(&b'(') => pos = self.arithmetic(pos + 2),
+                b';' | b'&' | b'|' | b'(' | b')' => {
+                    pos += 1;
+                    command_start = true;
+                    declared_usage = None;
+                },
+                b'<' if self.bytes[pos..].starts_with(b"<<") && !self.bytes[pos..].starts_with(b"<<<") => pos = self.heredoc_operator(pos + 2),
+                b'<' | b'>' => pos += 1,
+                _ => {
+                    let end = self.word(pos);
+                    let word = &self.source[pos..end];
+                    let name_end = pos + name_length(word.as_bytes());
+
+                    if loop_variable {
+                        if name_end == end && name_end > pos {
+                            self.push(pos..name_end, Usage::Definition);
+                        }
+                        loop_variable = false;
+                    } else if (command_start || declared_usage.is_some()) && name_end > pos && is_assignment(&self.bytes[name_end..end]) {
+                        self.push(pos..name_end, Usage::Definition);
+                    } else if let Some(usage) = declared_usage {
+                        if name_end == end && name_end > pos {
+                            self.push(pos..name_end, usage);
+                        }
+                    } else if command_start {
+                        if DECLARATION_COMMANDS.contains(&word) {
+                            declared_usage = Some(if word == "unset" { Usage::Reference } else { Usage::Definition });
+                            command_start = false;
+                        } else if word == "for" || word == "select" {
+                            loop_variable = true;
+                            command_start = false;
+                        } else if !COMMAND_PREFIXES.contains(&word) {
+                            command_start = false;
+                        }
+                    }
+                    pos = end;
+                },
+            }
+        }
+    }
+
+    fn push(&mut self, span: Range<usize>, usage: Usage) {
+        self.variables.push(Variable { name: &self.source[span.clone()], span, usage });
+    }
+
+    /// Where the character escaped by the backslash at `pos` ends
+    fn after_escape(&self, pos: usize) -> usize {
+        pos + 1 + self.source[pos + 1..].chars().next().map(char::len_utf8).unwrap_or(0)
+    }
+
+    fn find_byte(&self, from: usize, byte: u8) -> usize {
+        self.bytes[from..].iter().position(|it| *it == byte).map(|it| from + it).unwrap_or(self.bytes.len())
+    }
+
+    /// Scans a word, recording the variables it references, and returns where it ends
+    fn word(&mut self, mut pos: usize) -> usize {
+        while pos < self.bytes.len() {
+            match self.bytes[pos] {
+                b' ' | b'\t' | b'\n' | b';' | b'&' | b'|' | b'(' | b')' | b'<' | b'>' => break,
+                b'\\' => pos = self.after_escape(pos),
+                b'\'' => pos = self.find_byte(pos + 1, b'\'') + 1,
+                b'"' => pos = self.double_quoted(pos + 1),
+                b'`' => pos = self.expanding_until(pos + 1, b'`') + 1,
+                b'$' => pos = self.expansion(pos),
+                _ => pos += 1,
+            }
+        }
+        pos.min(self.bytes.len())
+    }
+
+    fn double_quoted(&mut self, pos: usize) -> usize {
+        self.expanding_until(pos, b'"') + 1
+    }
+
+    /// Records the variables referenced until the closing byte, returning its position
+    fn expanding_until(&mut self, mut pos: usize, closing: u8) -> usize {
+        while pos < self.bytes.len() && self.bytes[pos] != closing {
+            match self.bytes[pos] {
+                b'\\' => pos = self.after_escape(pos),
+                b'$' => pos = self.expansion(pos),
+                _ => pos += 1,
+            }
+        }
+        pos.min(self.bytes.len())
+    }
+
+    /// Scans an expansion starting with `$`, returning where it ends
+    fn expansion(&mut self, pos: usize) -> usize {
+        match self.bytes.get(pos + 1) {
+            Some(b'{') => {
+                let mut start = pos + 2;
+                if matches!(self.bytes.get(start), Some(b'!' | b'#')) {
+                    start += 1;
+                }
+                let end = start + name_length(&self.bytes[start..]);
+                if end > start {
+                    self.push(start..end, Usage::Reference);
+                }
+                // The rest, such as a default value, may reference other variables
+                let mut pos = end;
+                while pos < self.bytes.len() && self.bytes[pos] != b'}' {
+                    match self.bytes[pos] {
+                        b'\\' => pos = self.after_escape(pos),
+                        b'\'' => pos = self.find_byte(pos + 1, b'\'') + 1,
+                        b'"' => pos = self.double_quoted(pos + 1),
+                        b'$' => pos = self.expansion(pos),
+                        _ => pos += 1,
+                    }
+                }
+                pos + 1
+            },
+            Some(b'(') if self.bytes.get(pos + 2) == Some(&b'(') => self.arithmetic(pos + 3),
+            Some(b'(') => {
+                // A command substitution, whose end is found by balancing parentheses
+                let mut depth = 1;
+                let mut pos = pos + 2;
+                while pos < self.bytes.len() && depth > 0 {
+                    match self.bytes[pos] {
+                        b'\\' => pos = self.after_escape(pos),
+                        b'\'' => pos = self.find_byte(pos + 1, b'\'') + 1,
+                        b'"' => pos = self.double_quoted(pos + 1),
+                        b'$' => pos = self.expansion(pos),
+                        byte => {
+                            if byte == b'(' {
+                                depth += 1;
+                            } else if byte == b')' {
+                                depth -= 1;
+                            }
+                            pos += 1;
+                        },
+                    }
+                }
+                pos
+            },
+            _ => {
+                let start = pos + 1;
+                let end = start + name_length(&self.bytes[start..]);
+                if end > start {
+                    self.push(start..end, Usage::Reference);
+                    end
+                } else {
+                    // Special parameters such as $1, $? or $@
+                    start + 1
+                }
+            },
+        }
+    }
+
+    /// Scans an arithmetic expression, where bare names are variables, returning where it ends
+    fn arithmetic(&mut self, mut pos: usize) -> usize {
+        let mut depth = 0;
+        while pos < self.bytes.len() {
+            match self.bytes[pos] {
+                b')' if depth == 0 => return (pos + 2).min(self.bytes.len()),
+                b'(' => {
+                    depth += 1;
+                    pos += 1;
+                },
+                b')' => {
+                    depth -= 1;
+                    pos += 1;
+                },
+                b'$' => pos = self.expansion(pos),
+                byte if byte.is_ascii_digit() => {
+                    while pos < self.bytes.len() && self.bytes[pos].is_ascii_alphanumeric() {
+                        pos += 1;
+                    }
+                },
+                _ => {
+                    let end = pos + name_length(&self.bytes[pos..]);
+                    if end > pos {
+                        self.push(pos..end, Usage::Reference);
+                        pos = end;
+                    } else {
+                        pos += 1;
+                    }
+                },
+            }
+        }
+        pos
+    }
+
+    /// Scans the delimiter of a here-document after `<<`, returning where it ends
+    fn heredoc_operator(&mut self, mut pos: usize) -> usize {
+        let strips_tabs = self.bytes.get(pos) == Some(&b'-');
+        if strips_tabs {
+            pos += 1;
+        }
+        while matches!(self.bytes.get(pos), Some(b' ' | b'\t')) {
+            pos += 1;
+        }
+
+        let start = pos;
+        let mut delimiter = String::new();
+        let mut expands = true;
+        while pos < self.bytes.len() && !b" \t\n;&|()<>".contains(&self.bytes[pos]) {
+            match self.bytes[pos] {
+                quote @ (b'\'' | b'"') => {
+                    expands = false;
+                    let end = self.find_byte(pos + 1, quote);
+                    delimiter.push_str(&self.source[pos + 1..end]);
+                    pos = end + 1;
+                },
+                b'\\' => {
+                    expands = false;
+                    pos += 1;
+                },
+                _ => {
+                    let length = self.source[pos..].chars().next().map(char::len_utf8).unwrap_or(1);
+                    delimiter.push_str(&self.source[pos..pos + length]);
+                    pos += length;
+                },
+            }
+        }
+
+        if pos > start {
+            self.heredocs.push(Heredoc { delimiter, expands, strips_tabs });
+        }
+        pos.min(self.bytes.len())
+    }
+
+    /// Scans the bodies of the here-documents started on the previous line, returning where they end
+    fn heredoc_bodies(&mut self, mut pos: usize) -> usize {
+        for heredoc in std::mem::take(&mut self.heredocs) {
+            while pos < self.bytes.len() {
+                let line_end = self.find_byte(pos, b'\n');
+                let line = &self.source[pos..line_end];
+                let line = if heredoc.strips_tabs { line.trim_start_matches('\t') } else { line };
+
+                if line == heredoc.delimiter {
+                    pos = line_end + 1;
+                    break;
+                }
+                if heredoc.expands {
+                    self.expanding_until(pos, b'\n');
+                }
+                pos = line_end + 1;
+            }
+        }
+        pos
+    }
+}
+
+/// Length of the variable name at the start of `bytes`, or 0 if there's none
+fn name_length(bytes: &[u8]) -> usize {
+    match bytes.first() {
+        Some(byte) if byte.is_ascii_alphabetic() || *byte == b'_' => {
+            bytes.iter().take_while(|byte| byte.is_ascii_alphanumeric() || **byte == b'_').count()
+        },
+        _ => 0,
+    }
+}
+
+/// Whether what follows a variable name makes it an assignment, as in `=1`, `+=1` or `[0]=1`
+fn is_assignment(rest: &[u8]) -> bool {
+    let rest = if rest.first() == Some(&b'[') {
+        match rest.iter().position(|byte| *byte == b']') {
+            Some(end) => &rest[end + 1..],
+            None => return false,
+        }
+    } else {
+        rest
+    };
+
+    rest.starts_with(b"=") || rest.starts_with(b"+=")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    fn names(source: &str, usage: Usage) -> Vec<&str> {
+        variables(source).into_iter()
+                         .filter(|variable| variable.usage == usage)
+                         .map(|variable| variable.name)
+                         .collect()
+    }
+
+    #[test]
+    fn finds_env_file_variables() {
+        // ARRANGE
+        let source = indoc! {r#"
+            # dbHost=commented
+            dbHost=localhost
+            export dbPort="5432"
+            db_url='postgres://$dbHost'
+            apiUrl="http://${dbHost}:$dbPort/someValue"
+        "#};
+
+        // ACT
+        let variables = variables(source);
+
+        // ASSERT
+        assert_eq!(variables.iter().map(|it| (it.name, it.usage)).collect::<Vec<_>>(), vec![
+            ("dbHost", Usage::Definition),
+            ("dbPort", Usage::Definition),
+            ("db_url", Usage::Definition),
+            ("apiUrl", Usage::Definition),
+            ("dbHost", Usage::Reference),
+            ("dbPort", Usage::Reference),
+        ]);
+        assert_eq!(&source[variables[4].span.clone()], "dbHost");
+    }
+
+    #[test]
+    fn finds_shell_script_variables() {
+        // ARRANGE
+        let source = indoc! {r#"
+            retryCount=0 logLevel=debug runTask --retryCount=3 someValue
+            for itemName in "$@"; do
+                echo "${itemName:-$defaultName}" $(basename "$filePath") 'not $quoted'
+                (( retryCount += stepSize ))
+            done
+            local -r maxRetries=$((retryCount * 2))
+            read -r userInput
+            unset tempValue
+            cat <<EOF > "$outFile"
+            body with $bodyVar
+            notAssigned=1
+            EOF
+            cat <<'EOF'
+            literal $notAVariable
+            EOF
+        "#};
+
+        // ACT
+        let definitions = names(source, Usage::Definition);
+        let references = names(source, Usage::Reference);
+
+        // ASSERT
+        assert_eq!(definitions, vec!["retryCount", "logLevel", "itemName", "maxRetries", "userInput"]);
+        assert_eq!(references, vec![
+            "itemName", "defaultName", "filePath", "retryCount", "stepSize", "retryCount",
+            "tempValue", "outFile", "bodyVar",
+        ]);
+    }
+}
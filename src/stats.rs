@@ -0,0 +1,113 @@
+use clap::ValueEnum;
+use std::fmt::{Formatter, Display, Result as FormatResult};
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
+pub enum ConfidenceLevel {
+    #[value(name = "90", help = "90% confidence")]
+    Ninety,
+    #[value(name = "95", help = "95% confidence")]
+    NinetyFive,
+    #[value(name = "99", help = "99% confidence")]
+    NinetyNine,
+}
+
+impl ConfidenceLevel {
+    /// The confidence level as a number between 0 and 1
+    pub fn value(&self) -> f64 {
+        match self {
+            ConfidenceLevel::Ninety => 0.90,
+            ConfidenceLevel::NinetyFive => 0.95,
+            ConfidenceLevel::NinetyNine => 0.99,
+        }
+    }
+
+    /// The number of standard deviations of a normal distribution around its mean
+    /// that cover this share of it
+    fn z(&self) -> f64 {
+        match self {
+            ConfidenceLevel::Ninety => 1.6448536,
+            ConfidenceLevel::NinetyFive => 1.9599640,
+            ConfidenceLevel::NinetyNine => 2.5758293,
+        }
+    }
+}
+
+impl Display for ConfidenceLevel {
+    fn fmt(&self, f: &mut Formatter) -> FormatResult {
+        write!(f, "{}%", self.value() * 100.0)
+    }
+}
+
+/// Wilson score interval for the proportion of `successes` in `total` trials.
+/// Unlike the usual normal approximation, it stays within 0..1 and works for
+/// small samples and proportions close to 0 or 1
+pub fn wilson_interval(successes: u32, total: u32, level: ConfidenceLevel) -> (f64, f64) {
+    if total == 0 {
+        return (0.0, 1.0);
+    }
+
+    let n = f64::from(total);
+    let p = f64::from(successes) / n;
+    let z = level.z();
+    let z2 = z * z;
+
+    let denominator = 1.0 + z2 / n;
+    let center = (p + z2 / (2.0 * n)) / denominator;
+    let half_width = z / denominator * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt();
+    ((center - half_width).max(0.0), (center + half_width).min(1.0))
+}
+
+/// One-sided p-value of an exact sign test: the probability that `wins` or more of
+/// `wins + losses` fair coin flips come up the same side. Small values mean
+/// `wins` is larger than `losses` not just by chance
+pub fn sign_test(wins: u32, losses: u32) -> f64 {
+    let n = wins + losses;
+    if n == 0 {
+        return 1.0;
+    }
+
+    // ln(k!) for every k up to n, so binomial coefficients don't overflow
+    let mut ln_factorials = Vec::with_capacity(n as usize + 1);
+    ln_factorials.push(0.0);
+    for k in 1..=n {
+        ln_factorials.push(ln_factorials[k as usize - 1] + f64::from(k).ln());
+    }
+    let ln_half_power = f64::from(n) * 0.5_f64.ln();
+
+    let p_value: f64 = (wins..=n).map(|k| {
+                                      let ln_binomial = ln_factorials[n as usize] - ln_factorials[k as usize] - ln_factorials[(n - k) as usize];
+                                      (ln_binomial + ln_half_power).exp()
+                                  })
+                                  .sum();
+    p_value.min(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn wilson_interval_of_sample() {
+        let (low, high) = wilson_interval(8, 10, ConfidenceLevel::NinetyFive);
+
+        assert_relative_eq!(low, 0.4902, epsilon = 0.0001);
+        assert_relative_eq!(high, 0.9433, epsilon = 0.0001);
+    }
+
+    #[test]
+    fn wilson_interval_stays_within_bounds() {
+        let (low, high) = wilson_interval(5, 5, ConfidenceLevel::NinetyNine);
+
+        assert_relative_eq!(high, 1.0);
+        assert!(low > 0.0 && low < 1.0);
+    }
+
+    #[test]
+    fn sign_test_p_values() {
+        assert_relative_eq!(sign_test(9, 1), 11.0 / 1024.0, epsilon = 1e-12);
+        assert_relative_eq!(sign_test(3, 3), 0.65625, epsilon = 1e-12);
+        assert_relative_eq!(sign_test(4, 0), 0.0625, epsilon = 1e-12);
+        assert!(sign_test(6000, 4000) < 1e-80);
+    }
+}
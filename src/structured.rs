@@ -0,0 +1,762 @@
+use clap::ValueEnum;
+use std::collections::HashMap;
+use std::fmt::{Formatter, Display, Result as FormatResult};
+use std::ops::Range;
+use std::path::Path;
+use std::str::FromStr;
+use thiserror::Error;
+use yaml_rust2::parser::Parser as YamlParser;
+use yaml_rust2::scanner::TScalarStyle;
+use yaml_rust2::Event as YamlEvent;
+use crate::scan;
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, ValueEnum)]
+pub enum Format {
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl Format {
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Format> {
+        match path.as_ref().extension()?.to_str()? {
+            "json" => Some(Format::Json),
+            "yaml" | "yml" => Some(Format::Yaml),
+            "toml" => Some(Format::Toml),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Format {
+    fn fmt(&self, f: &mut Formatter) -> FormatResult {
+        write!(f, "{}", match self {
+            Format::Json => "JSON",
+            Format::Yaml => "YAML",
+            Format::Toml => "TOML",
+        })
+    }
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum StructuredError {
+    #[error("invalid {0} at line {1}, column {2}: {3}")]
+    InvalidInputError(Format, usize, usize, String),
+    #[error("invalid path '{0}': {1}")]
+    InvalidPathError(String, String),
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+/// Formats a path like `$.data.items[0].name`
+pub fn display_path(path: &[PathSegment]) -> String {
+    let mut result = String::from("$");
+    for segment in path {
+        match segment {
+            PathSegment::Key(name) => {
+                result.push('.');
+                result.push_str(name);
+            },
+            PathSegment::Index(index) => result.push_str(&format!("[{index}]")),
+        }
+    }
+    result
+}
+
+/// A key of an object in some structured document. `span` is the byte span of the
+/// key's text in the document, excluding quotes, and `path` ends with the key itself
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Key {
+    pub name: String,
+    pub span: Range<usize>,
+    pub path: Vec<PathSegment>,
+}
+
+impl Key {
+    /// How many objects deep the key is, starting at 1 for top-level keys
+    pub fn depth(&self) -> usize {
+        self.path.iter().filter(|it| matches!(it, PathSegment::Key(_))).count()
+    }
+
+    /// The path of the object the key belongs to
+    pub fn object_path(&self) -> &[PathSegment] {
+        &self.path[..self.path.len() - 1]
+    }
+}
+
+/// Finds every key of every object in `source`, in the order they appear
+pub fn keys(source: &str, format: Format) -> Result<Vec<Key>, StructuredError> {
+    match format {
+        Format::Json => JsonScanner::new(source).scan(),
+        Format::Yaml => yaml_keys(source),
+        Format::Toml => TomlScanner::new(source).scan(),
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+enum PatternSegment {
+    Key(String),
+    Index(usize),
+    AnyIndex,
+    Any,
+    Descendants,
+}
+
+/// A simplified JSONPath such as `$.data.*`, `$.items[*].id` or `$..id`
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct PathPattern(Vec<PatternSegment>);
+
+impl FromStr for PathPattern {
+    type Err = StructuredError;
+
+    fn from_str(pattern: &str) -> Result<Self, Self::Err> {
+        let error = |message: &str| StructuredError::InvalidPathError(String::from(pattern), String::from(message));
+        let mut rest = pattern.strip_prefix('$').ok_or_else(|| error("it must start with '$'"))?;
+        let mut segments = Vec::new();
+
+        while !rest.is_empty() {
+            let name_end = |text: &str| text.find(['.', '[']).unwrap_or(text.len());
+
+            if let Some(after) = rest.strip_prefix("..") {
+                segments.push(PatternSegment::Descendants);
+                let end = name_end(after);
+                match &after[..end] {
+                    "" => return Err(error("'..' must be followed by a key")),
+                    "*" => segments.push(PatternSegment::Any),
+                    name => segments.push(PatternSegment::Key(String::from(name))),
+                }
+                rest = &after[end..];
+            } else if let Some(after) = rest.strip_prefix('.') {
+                let end = name_end(after);
+                match &after[..end] {
+                    "" => return Err(error("'.' must be followed by a key")),
+                    "*" => segments.push(PatternSegment::Any),
+                    name => segments.push(PatternSegment::Key(String::from(name))),
+                }
+                rest = &after[end..];
+            } else if let Some(after) = rest.strip_prefix('[') {
+                let end = after.find(']').ok_or_else(|| error("unclosed '['"))?;
+                match &after[..end] {
+                    "*" => segments.push(PatternSegment::AnyIndex),
+                    index => segments.push(PatternSegment::Index(index.parse().map_err(|_| error("invalid index"))?)),
+                }
+                rest = &after[end + 1..];
+            } else {
+                return Err(error("expected '.' or '['"));
+            }
+        }
+
+        Ok(PathPattern(segments))
+    }
+}
+
+impl PathPattern {
+    pub fn matches(&self, path: &[PathSegment]) -> bool {
+        fn matches_from(pattern: &[PatternSegment], path: &[PathSegment]) -> bool {
+            match (pattern.first(), path.first()) {
+                (None, _) => path.is_empty(),
+                (Some(PatternSegment::Descendants), _) => {
+                    matches_from(&pattern[1..], path) || (!path.is_empty() && matches_from(pattern, &path[1..]))
+                },
+                (Some(_), None) => false,
+                (Some(segment), Some(first)) => {
+                    let segment_matches = match (segment, first) {
+                        (PatternSegment::Any, _) => true,
+                        (PatternSegment::AnyIndex, PathSegment::Index(_)) => true,
+                        (PatternSegment::Key(name), PathSegment::Key(key)) => name == key,
+                        (PatternSegment::Index(index), PathSegment::Index(actual)) => index == actual,
+                        _ => false,
+                    };
+                    segment_matches && matches_from(&pattern[1..], &path[1..])
+                },
+            }
+        }
+
+        matches_from(&self.0, path)
+    }
+}
+
+/// Which keys of a document to consider
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct KeyFilter {
+    /// Only keys at most this many objects deep
+    pub max_depth: Option<usize>,
+    /// Only keys whose path matches any of these patterns (or all of them if empty)
+    pub paths: Vec<PathPattern>,
+}
+
+impl KeyFilter {
+    pub fn matches(&self, key: &Key) -> bool {
+        self.max_depth.map(|max_depth| key.depth() <= max_depth).unwrap_or(true)
+            && (self.paths.is_empty() || self.paths.iter().any(|pattern| pattern.matches(&key.path)))
+    }
+}
+
+fn invalid_input(format: Format, source: &str, offset: usize, message: &str) -> StructuredError {
+    let (line, column) = scan::line_column(source, offset.min(source.len()));
+    StructuredError::InvalidInputError(format, line, column, String::from(message))
+}
+
+struct JsonScanner<'a> {
+    source: &'a str,
+    bytes: &'a [u8],
+    pos: usize,
+    keys: Vec<Key>,
+}
+
+impl <'a> JsonScanner<'a> {
+    fn new(source: &'a str) -> Self {
+        JsonScanner { source, bytes: source.as_bytes(), pos: 0, keys: Vec::new() }
+    }
+
+    fn scan(mut self) -> Result<Vec<Key>, StructuredError> {
+        self.value(&mut Vec::new())?;
+        self.skip_whitespace();
+        if self.pos < self.bytes.len() {
+            return Err(self.error("unexpected trailing characters"));
+        }
+        Ok(self.keys)
+    }
+
+    fn error(&self, message: &str) -> StructuredError {
+        invalid_input(Format::Json, self.source, self.pos, message)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.pos < self.bytes.len() && self.bytes[self.pos].is_ascii_whitespace() {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), StructuredError> {
+        self.skip_whitespace();
+        if self.bytes.get(self.pos) != Some(&byte) {
+            return Err(self.error(&format!("expected '{}'", byte as char)));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    /// Skips a string, returning the span of its contents
+    fn string(&mut self) -> Result<Range<usize>, StructuredError> {
+        self.expect(b'"')?;
+        let start = self.pos;
+        while self.pos < self.bytes.len() {
+            match self.bytes[self.pos] {
+                b'\\' => self.pos += 2,
+                b'"' => {
+                    self.pos += 1;
+                    return Ok(start..self.pos - 1);
+                },
+                _ => self.pos += 1,
+            }
+        }
+        Err(self.error("unterminated string"))
+    }
+
+    fn value(&mut self, path: &mut Vec<PathSegment>) -> Result<(), StructuredError> {
+        self.skip_whitespace();
+        match self.bytes.get(self.pos) {
+            Some(b'{') => {
+                self.pos += 1;
+                self.skip_whitespace();
+                if self.bytes.get(self.pos) == Some(&b'}') {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                loop {
+                    self.skip_whitespace();
+                    let span = self.string()?;
+                    let name = String::from(&self.source[span.clone()]);
+                    path.push(PathSegment::Key(name.clone()));
+                    self.keys.push(Key { name, span, path: path.clone() });
+                    self.expect(b':')?;
+                    self.value(path)?;
+                    path.pop();
+
+                    self.skip_whitespace();
+                    match self.bytes.get(self.pos) {
+                        Some(b',') => self.pos += 1,
+                        Some(b'}') => {
+                            self.pos += 1;
+                            return Ok(());
+                        },
+                        _ => return Err(self.error("expected ',' or '}'")),
+                    }
+                }
+            },
+            Some(b'[') => {
+                self.pos += 1;
+                self.skip_whitespace();
+                if self.bytes.get(self.pos) == Some(&b']') {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                for index in 0.. {
+                    path.push(PathSegment::Index(index));
+                    self.value(path)?;
+                    path.pop();
+
+                    self.skip_whitespace();
+                    match self.bytes.get(self.pos) {
+                        Some(b',') => self.pos += 1,
+                        Some(b']') => {
+                            self.pos += 1;
+                            break;
+                        },
+                        _ => return Err(self.error("expected ',' or ']'")),
+                    }
+                }
+                Ok(())
+            },
+            Some(b'"') => self.string().map(|_| ()),
+            Some(_) => {
+                let start = self.pos;
+                while self.pos < self.bytes.len() && (self.bytes[self.pos].is_ascii_alphanumeric() || b"+-.".contains(&self.bytes[self.pos])) {
+                    self.pos += 1;
+                }
+                if start == self.pos {
+                    return Err(self.error("expected a value"));
+                }
+                Ok(())
+            },
+            None => Err(self.error("expected a value")),
+        }
+    }
+}
+
+fn yaml_keys(source: &str) -> Result<Vec<Key>, StructuredError> {
+    enum Frame {
+        Mapping { expecting_key: bool },
+        Sequence { next_index: usize },
+    }
+
+    // The parser reports positions in chars
+    let mut byte_offsets: Vec<usize> = source.char_indices().map(|(offset, _)| offset).collect();
+    byte_offsets.push(source.len());
+
+    let mut parser = YamlParser::new_from_str(source);
+    let mut keys = Vec::new();
+    let mut frames: Vec<Frame> = Vec::new();
+    let mut path: Vec<PathSegment> = Vec::new();
+
+    loop {
+        let (event, marker) = parser.next_token().map_err(|err| {
+            let offset = byte_offsets.get(err.marker().index()).copied().unwrap_or(source.len());
+            invalid_input(Format::Yaml, source, offset, err.info())
+        })?;
+
+        let (starts_node, ends_node) = match &event {
+            YamlEvent::StreamEnd => break,
+            YamlEvent::Scalar(..) | YamlEvent::Alias(_) => (true, true),
+            YamlEvent::MappingStart(..) | YamlEvent::SequenceStart(..) => (true, false),
+            YamlEvent::MappingEnd | YamlEvent::SequenceEnd => (false, true),
+            _ => (false, false),
+        };
+
+        if starts_node {
+            match frames.last_mut() {
+                Some(Frame::Mapping { expecting_key: true }) => {
+                    let name = match &event {
+                        YamlEvent::Scalar(name, style, ..) => {
+                            let mut start = byte_offsets.get(marker.index()).copied().unwrap_or(source.len());
+                            if matches!(style, TScalarStyle::SingleQuoted | TScalarStyle::DoubleQuoted) {
+                                start += 1;
+                            }
+                            // Keys whose text differs from their value (such as those with escapes) are left alone
+                            if source[start..].starts_with(name.as_str()) {
+                                let mut key_path = path.clone();
+                                key_path.push(PathSegment::Key(name.clone()));
+                                keys.push(Key { name: name.clone(), span: start..start + name.len(), path: key_path });
+                            }
+                            name.clone()
+                        },
+                        _ => String::new(),
+                    };
+                    path.push(PathSegment::Key(name));
+                },
+                Some(Frame::Sequence { next_index }) => {
+                    path.push(PathSegment::Index(*next_index));
+                    *next_index += 1;
+                },
+                _ => {},
+            }
+        }
+
+        match event {
+            YamlEvent::MappingStart(..) => frames.push(Frame::Mapping { expecting_key: true }),
+            YamlEvent::SequenceStart(..) => frames.push(Frame::Sequence { next_index: 0 }),
+            YamlEvent::MappingEnd | YamlEvent::SequenceEnd => {
+                frames.pop();
+            },
+            _ => {},
+        }
+
+        if ends_node {
+            match frames.last_mut() {
+                Some(Frame::Mapping { expecting_key }) => {
+                    if !*expecting_key {
+                        path.pop();
+                    }
+                    *expecting_key = !*expecting_key;
+                },
+                Some(Frame::Sequence { .. }) => {
+                    path.pop();
+                },
+                None => {},
+            }
+        }
+    }
+
+    Ok(keys)
+}
+
+struct TomlScanner<'a> {
+    source: &'a str,
+    bytes: &'a [u8],
+    pos: usize,
+    keys: Vec<Key>,
+    /// How many tables each array of tables has so far
+    array_tables: HashMap<Vec<PathSegment>, usize>,
+}
+
+impl <'a> TomlScanner<'a> {
+    fn new(source: &'a str) -> Self {
+        TomlScanner { source, bytes: source.as_bytes(), pos: 0, keys: Vec::new(), array_tables: HashMap::new() }
+    }
+
+    fn scan(mut self) -> Result<Vec<Key>, StructuredError> {
+        let mut table_path: Vec<PathSegment> = Vec::new();
+
+        loop {
+            self.skip_whitespace(true);
+            let Some(byte) = self.bytes.get(self.pos) else { break };
+
+            if *byte == b'[' {
+                let is_array = self.bytes.get(self.pos + 1) == Some(&b'[');
+                self.pos += if is_array { 2 } else { 1 };
+
+                let dotted_key = self.dotted_key()?;
+                let mut path = Vec::new();
+                for (i, (name, span)) in dotted_key.iter().enumerate() {
+                    path.push(PathSegment::Key(name.clone()));
+                    self.keys.push(Key { name: name.clone(), span: span.clone(), path: path.clone() });
+
+                    if is_array && i == dotted_key.len() - 1 {
+                        let count = self.array_tables.entry(path.clone()).or_insert(0);
+                        *count += 1;
+                        path.push(PathSegment::Index(*count - 1));
+                    } else if let Some(count) = self.array_tables.get(&path) {
+                        path.push(PathSegment::Index(count - 1));
+                    }
+                }
+
+                self.expect(b']')?;
+                if is_array {
+                    self.expect(b']')?;
+                }
+                table_path = path;
+            } else {
+                self.key_value(&mut table_path.clone())?;
+            }
+
+            self.skip_whitespace(false);
+            match self.bytes.get(self.pos) {
+                None | Some(b'\n') => {},
+                Some(b'\r') if self.bytes.get(self.pos + 1) == Some(&b'\n') => {},
+                _ => return Err(self.error("expected a new line")),
+            }
+        }
+
+        Ok(self.keys)
+    }
+
+    fn error(&self, message: &str) -> StructuredError {
+        invalid_input(Format::Toml, self.source, self.pos, message)
+    }
+
+    /// Skips whitespace and comments, and also new lines if `newlines` is set
+    fn skip_whitespace(&mut self, newlines: bool) {
+        while let Some(byte) = self.bytes.get(self.pos) {
+            match byte {
+                b' ' | b'\t' => self.pos += 1,
+                b'\n' | b'\r' if newlines => self.pos += 1,
+                b'#' => {
+                    while self.pos < self.bytes.len() && self.bytes[self.pos] != b'\n' {
+                        self.pos += 1;
+                    }
+                },
+                _ => break,
+            }
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), StructuredError> {
+        self.skip_whitespace(false);
+        if self.bytes.get(self.pos) != Some(&byte) {
+            return Err(self.error(&format!("expected '{}'", byte as char)));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn dotted_key(&mut self) -> Result<Vec<(String, Range<usize>)>, StructuredError> {
+        let mut result = Vec::new();
+        loop {
+            self.skip_whitespace(false);
+            let span = match self.bytes.get(self.pos) {
+                Some(b'"' | b'\'') => {
+                    let span = self.string()?;
+                    span.start + 1..span.end - 1
+                },
+                _ => {
+                    let start = self.pos;
+                    while self.pos < self.bytes.len() && (self.bytes[self.pos].is_ascii_alphanumeric() || b"_-".contains(&self.bytes[self.pos])) {
+                        self.pos += 1;
+                    }
+                    if start == self.pos {
+                        return Err(self.error("expected a key"));
+                    }
+                    start..self.pos
+                },
+            };
+            result.push((String::from(&self.source[span.clone()]), span));
+
+            self.skip_whitespace(false);
+            if self.bytes.get(self.pos) == Some(&b'.') {
+                self.pos += 1;
+            } else {
+                return Ok(result);
+            }
+        }
+    }
+
+    fn key_value(&mut self, path: &mut Vec<PathSegment>) -> Result<(), StructuredError> {
+        let dotted_key = self.dotted_key()?;
+        let depth = path.len();
+        for (name, span) in dotted_key {
+            path.push(PathSegment::Key(name.clone()));
+            self.keys.push(Key { name, span, path: path.clone() });
+        }
+        self.expect(b'=')?;
+        self.value(path)?;
+        path.truncate(depth);
+        Ok(())
+    }
+
+    /// Skips a string of any kind, returning its span including quotes
+    fn string(&mut self) -> Result<Range<usize>, StructuredError> {
+        let start = self.pos;
+        let quote = self.bytes[self.pos];
+        let multiline = self.bytes[self.pos..].starts_with(&[quote; 3]);
+        let closing: &[u8] = if multiline { &self.bytes[start..start + 3] } else { &self.bytes[start..start + 1] };
+        self.pos += closing.len();
+
+        while self.pos < self.bytes.len() {
+            if quote == b'"' && self.bytes[self.pos] == b'\\' {
+                self.pos += 2;
+            } else if self.bytes[self.pos..].starts_with(closing) {
+                self.pos += closing.len();
+                // Multi-line strings may end with up to two extra quotes
+                while multiline && self.bytes.get(self.pos) == Some(&quote) && self.pos - start < closing.len() * 2 + 2 {
+                    self.pos += 1;
+                }
+                return Ok(start..self.pos);
+            } else if !multiline && self.bytes[self.pos] == b'\n' {
+                break;
+            } else {
+                self.pos += 1;
+            }
+        }
+        Err(self.error("unterminated string"))
+    }
+
+    fn value(&mut self, path: &mut Vec<PathSegment>) -> Result<(), StructuredError> {
+        self.skip_whitespace(false);
+        match self.bytes.get(self.pos) {
+            Some(b'"' | b'\'') => self.string().map(|_| ()),
+            Some(b'[') => {
+                self.pos += 1;
+                for index in 0.. {
+                    self.skip_whitespace(true);
+                    if self.bytes.get(self.pos) == Some(&b']') {
+                        break;
+                    }
+                    path.push(PathSegment::Index(index));
+                    self.value(path)?;
+                    path.pop();
+
+                    self.skip_whitespace(true);
+                    match self.bytes.get(self.pos) {
+                        Some(b',') => self.pos += 1,
+                        Some(b']') => break,
+                        _ => return Err(self.error("expected ',' or ']'")),
+                    }
+                }
+                self.pos += 1;
+                Ok(())
+            },
+            Some(b'{') => {
+                self.pos += 1;
+                loop {
+                    self.skip_whitespace(true);
+                    if self.bytes.get(self.pos) == Some(&b'}') {
+                        break;
+                    }
+                    self.key_value(path)?;
+
+                    self.skip_whitespace(true);
+                    match self.bytes.get(self.pos) {
+                        Some(b',') => self.pos += 1,
+                        Some(b'}') => break,
+                        _ => return Err(self.error("expected ',' or '}'")),
+                    }
+                }
+                self.pos += 1;
+                Ok(())
+            },
+            Some(_) => {
+                // Numbers, booleans and dates, which may contain a space between date and time
+                let start = self.pos;
+                while self.pos < self.bytes.len() && !b",]}#\r\n".contains(&self.bytes[self.pos]) {
+                    self.pos += 1;
+                }
+                while self.pos > start && b" \t".contains(&self.bytes[self.pos - 1]) {
+                    self.pos -= 1;
+                }
+                if start == self.pos {
+                    return Err(self.error("expected a value"));
+                }
+                Ok(())
+            },
+            None => Err(self.error("expected a value")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    fn paths(keys: &[Key]) -> Vec<String> {
+        keys.iter().map(|key| display_path(&key.path)).collect()
+    }
+
+    #[test]
+    fn finds_json_keys() -> Result<(), StructuredError> {
+        // ARRANGE
+        let source = r#"{"userId": 1, "data": {"items": [{"itemName": "firstName"}, 2]}, "empty": {}}"#;
+
+        // ACT
+        let keys = keys(source, Format::Json)?;
+
+        // ASSERT
+        assert_eq!(paths(&keys), vec!["$.userId", "$.data", "$.data.items", "$.data.items[0].itemName", "$.empty"]);
+        assert_eq!(&source[keys[0].span.clone()], "userId");
+        assert_eq!(keys[3].depth(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_json() {
+        let result = keys("{\n  \"a\" 1\n}", Format::Json);
+
+        assert_eq!(result, Err(StructuredError::InvalidInputError(Format::Json, 2, 7, String::from("expected ':'"))));
+    }
+
+    #[test]
+    fn finds_yaml_keys() -> Result<(), StructuredError> {
+        // ARRANGE
+        let source = indoc! {r#"
+            userId: 1 # firstName: no
+            "quotedKey": {inlineKey: [1, {deepKey: 2}]}
+            list:
+              - itemName: value
+                description: |
+                  notAKey: text
+        "#};
+
+        // ACT
+        let keys = keys(source, Format::Yaml)?;
+
+        // ASSERT
+        assert_eq!(paths(&keys), vec![
+            "$.userId", "$.quotedKey", "$.quotedKey.inlineKey", "$.quotedKey.inlineKey[1].deepKey",
+            "$.list", "$.list[0].itemName", "$.list[0].description",
+        ]);
+        assert_eq!(&source[keys[1].span.clone()], "quotedKey");
+        Ok(())
+    }
+
+    #[test]
+    fn finds_toml_keys() -> Result<(), StructuredError> {
+        // ARRANGE
+        let source = indoc! {r#"
+            userName = "firstName = no" # lastName = no
+            server.hostName = """
+            multiLine = no"""
+
+            [database]
+            connectionPool = { maxSize = 10, minSize = [1, 2] }
+
+            [[database.replicas]]
+            replicaHost = 'one'
+
+            [[database.replicas]]
+            replicaHost = 'two'
+
+            [database.replicas.extraOptions]
+            "quotedKey" = 1979-05-27 07:32:00Z
+        "#};
+
+        // ACT
+        let keys = keys(source, Format::Toml)?;
+
+        // ASSERT
+        assert_eq!(paths(&keys), vec![
+            "$.userName", "$.server", "$.server.hostName",
+            "$.database", "$.database.connectionPool", "$.database.connectionPool.maxSize", "$.database.connectionPool.minSize",
+            "$.database", "$.database.replicas", "$.database.replicas[0].replicaHost",
+            "$.database", "$.database.replicas", "$.database.replicas[1].replicaHost",
+            "$.database", "$.database.replicas", "$.database.replicas[1].extraOptions", "$.database.replicas[1].extraOptions.quotedKey",
+        ]);
+        assert_eq!(&source[keys[16].span.clone()], "quotedKey");
+        Ok(())
+    }
+
+    #[test]
+    fn path_patterns() -> Result<(), StructuredError> {
+        let path = |keys: &[&str]| keys.iter().map(|key| match key.parse() {
+            Ok(index) => PathSegment::Index(index),
+            Err(_) => PathSegment::Key(String::from(*key)),
+        }).collect::<Vec<_>>();
+
+        assert!("$.data.*".parse::<PathPattern>()?.matches(&path(&["data", "userId"])));
+        assert!(!"$.data.*".parse::<PathPattern>()?.matches(&path(&["data", "user", "userId"])));
+        assert!("$.items[*].id".parse::<PathPattern>()?.matches(&path(&["items", "3", "id"])));
+        assert!(!"$.items[0].id".parse::<PathPattern>()?.matches(&path(&["items", "3", "id"])));
+        assert!("$..id".parse::<PathPattern>()?.matches(&path(&["a", "0", "b", "id"])));
+        assert!("data.*".parse::<PathPattern>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn key_filter() -> Result<(), StructuredError> {
+        let key = Key {
+            name: String::from("userId"),
+            span: 0..0,
+            path: vec![PathSegment::Key(String::from("data")), PathSegment::Index(0), PathSegment::Key(String::from("userId"))],
+        };
+
+        assert!(KeyFilter::default().matches(&key));
+        assert!(!KeyFilter { max_depth: Some(1), paths: vec![] }.matches(&key));
+        assert!(KeyFilter { max_depth: Some(2), paths: vec!["$.data.*.*".parse()?] }.matches(&key));
+        assert!(!KeyFilter { max_depth: None, paths: vec!["$.other.*".parse()?] }.matches(&key));
+        Ok(())
+    }
+}
@@ -0,0 +1,718 @@
+use clap::ValueEnum;
+use strum_macros::EnumIter;
+use std::fmt::{Formatter, Display, Result as FormatResult};
+use std::ops::Range;
+use std::path::Path;
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, ValueEnum)]
+pub enum Language {
+    Rust,
+    Python,
+    Sql,
+}
+
+impl Language {
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Language> {
+        match path.as_ref().extension()?.to_str()? {
+            "rs" => Some(Language::Rust),
+            "py" | "pyi" => Some(Language::Python),
+            "sql" => Some(Language::Sql),
+            _ => None,
+        }
+    }
+
+    fn keywords(&self) -> &'static [&'static str] {
+        match self {
+            Language::Rust => &[
+                "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else",
+                "enum", "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop",
+                "match", "mod", "move", "mut", "pub", "ref", "return", "self", "Self",
+                "static", "struct", "super", "trait", "true", "type", "union", "unsafe",
+                "use", "where", "while",
+            ],
+            Language::Python => &[
+                "False", "None", "True", "and", "as", "assert", "async", "await", "break",
+                "class", "continue", "def", "del", "elif", "else", "except", "finally", "for",
+                "from", "global", "if", "import", "in", "is", "lambda", "nonlocal", "not",
+                "or", "pass", "raise", "return", "try", "while", "with", "yield",
+            ],
+            // Including common type and function names, which are case-insensitive too
+            Language::Sql => &[
+                "action", "add", "all", "alter", "always", "analyze", "and", "any", "as", "asc",
+                "at", "auto_increment", "avg", "begin", "between", "bigint", "bigserial", "blob",
+                "bool", "boolean", "by", "bytea", "cascade", "case", "cast", "char", "character",
+                "check", "coalesce", "collate", "column", "comment", "commit", "conflict",
+                "constraint", "count", "create", "cross", "current_date", "current_timestamp",
+                "database", "date", "decimal", "default", "deferrable", "deferred", "delete",
+                "desc", "distinct", "do", "double", "drop", "each", "else", "end", "engine",
+                "enum", "escape", "except", "execute", "exists", "explain", "false", "fetch",
+                "filter", "first", "float", "for", "foreign", "from", "full", "function",
+                "generated", "grant", "group", "having", "identity", "if", "ilike", "immediate",
+                "in", "index", "initially", "inner", "insert", "int", "integer", "intersect",
+                "interval", "into", "is", "join", "json", "jsonb", "key", "language", "last",
+                "lateral", "left", "like", "limit", "local", "lower", "max", "min", "natural",
+                "next", "no", "not", "nothing", "now", "null", "nullif", "nulls", "numeric",
+                "offset", "on", "only", "or", "order", "outer", "over", "partition", "precision",
+                "primary", "procedure", "rank", "real", "recursive", "references", "rename",
+                "replace", "restrict", "returning", "returns", "revoke", "right", "rollback",
+                "row", "row_number", "rows", "schema", "select", "sequence", "serial", "set",
+                "similar", "smallint", "some", "stored", "sum", "table", "temp", "temporary",
+                "text", "then", "time", "timestamp", "timestamptz", "to", "transaction",
+                "trigger", "true", "truncate", "type", "union", "unique", "unsigned", "update",
+                "upper", "using", "uuid", "vacuum", "values", "varchar", "varying", "view",
+                "virtual", "when", "where", "window", "with", "without", "zone",
+            ],
+        }
+    }
+
+    fn is_keyword(&self, name: &str) -> bool {
+        match self {
+            Language::Sql => self.keywords().contains(&name.to_lowercase().as_str()),
+            _ => self.keywords().contains(&name),
+        }
+    }
+}
+
+#[derive(Debug, EnumIter, PartialEq, Eq, Hash, Clone, Copy, ValueEnum, Ord, PartialOrd)]
+pub enum IdentifierKind {
+    #[value(help = "Structs, enums, traits, classes, type parameters and SQL tables")]
+    Type,
+    #[value(help = "Function and method names")]
+    Function,
+    #[value(help = "Local variables and parameters")]
+    Variable,
+    #[value(help = "Constants and statics")]
+    Constant,
+    #[value(help = "Struct fields, attributes and SQL columns")]
+    Field,
+    #[value(help = "SQL column aliases given with AS")]
+    Alias,
+}
+
+impl Display for IdentifierKind {
+    fn fmt(&self, f: &mut Formatter) -> FormatResult {
+        write!(f, "{}", match self {
+            IdentifierKind::Type => "type",
+            IdentifierKind::Function => "function",
+            IdentifierKind::Variable => "variable",
+            IdentifierKind::Constant => "constant",
+            IdentifierKind::Field => "field",
+            IdentifierKind::Alias => "alias",
+        })
+    }
+}
+
+/// An identifier found in source code, along with its byte span in the source
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Identifier<'a> {
+    pub name: &'a str,
+    pub span: Range<usize>,
+    pub kind: IdentifierKind,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Token<'a> {
+    Identifier(usize, usize),
+    /// The contents of an identifier in quotes, such as `"userId"` in SQL, or of a
+    /// raw identifier, such as `r#type` in Rust. Neither is ever a keyword
+    QuotedIdentifier(usize, usize),
+    Punctuation(&'a str),
+    Literal,
+    Newline,
+}
+
+/// Finds all identifiers in `source`, skipping keywords, comments and
+/// the contents of string and character literals.
+/// Identifiers that don't fall into any `IdentifierKind` (such as macro
+/// or module names) are not returned.
+///
+/// Kinds are guessed from the tokens right next to each identifier and from
+/// its shape, without parsing the whole grammar or resolving names. So a
+/// variable holding a closure or function is a function where it's called, a
+/// PascalCase variable is a type, and an uppercase one is a constant.
+pub fn identifiers(source: &str, language: Language) -> Vec<Identifier<'_>> {
+    let tokens = tokenize(source, language);
+
+    let mut result: Vec<Identifier> = Vec::new();
+    // For each open bracket, whether it contains struct fields
+    let mut brackets: Vec<(char, bool)> = Vec::new();
+    let mut expecting_fields = false;
+    // Whether we are in the module path of an import statement
+    let mut in_import = false;
+    let mut in_from_import = false;
+
+    for (i, token) in tokens.iter().enumerate() {
+        let prev = if i > 0 { tokens[i - 1] } else { Token::Newline };
+        let next = tokens.get(i + 1).copied().unwrap_or(Token::Newline);
+
+        match *token {
+            Token::Punctuation(p @ ("(" | "[" | "{")) => {
+                let contains_fields = p == "{" && (expecting_fields || matches!(prev, Token::Identifier(start, _) if starts_uppercase(&source[start..])));
+                brackets.push((p.chars().next().unwrap(), contains_fields));
+                expecting_fields = false;
+            },
+            Token::Punctuation(")" | "]" | "}") => {
+                brackets.pop();
+            },
+            Token::Punctuation(";") => expecting_fields = false,
+            Token::Newline if brackets.is_empty() => {
+                in_import = false;
+                in_from_import = false;
+            },
+            Token::Identifier(start, end) | Token::QuotedIdentifier(start, end) => {
+                let name = &source[start..end];
+                if matches!(token, Token::Identifier(..)) && language.is_keyword(name) {
+                    match name {
+                        "struct" | "union" => expecting_fields = true,
+                        "from" if language == Language::Python => {
+                            in_import = true;
+                            in_from_import = true;
+                        },
+                        // Names imported from a module are regular identifiers
+                        "import" if language == Language::Python => in_import = !in_from_import,
+                        "as" if language == Language::Python => in_import = false,
+                        _ => {},
+                    }
+                    continue;
+                }
+
+                if in_import {
+                    continue;
+                }
+
+                let in_fields = matches!(brackets.last(), Some(('{', true)));
+                let kind = match language {
+                    Language::Rust => classify_rust(source, name, prev, next, in_fields),
+                    Language::Python => classify_python(source, name, prev, next),
+                    Language::Sql => {
+                        // Looking past AS, so an alias knows whether it names a table
+                        let after_as = matches!(prev, Token::Identifier(start, end) if source[start..end].eq_ignore_ascii_case("as"));
+                        let named = if after_as && i >= 2 { tokens[i - 2] } else { prev };
+                        let prev_kind = match named {
+                            Token::Identifier(_, end) | Token::QuotedIdentifier(_, end) => {
+                                result.last().filter(|it| it.span.end == end).map(|it| it.kind)
+                            },
+                            _ => None,
+                        };
+                        classify_sql(source, prev, prev_kind, next)
+                    },
+                };
+
+                if let Some(kind) = kind {
+                    result.push(Identifier { name, span: start..end, kind });
+                }
+            },
+            _ => {},
+        }
+    }
+
+    result
+}
+
+const RUST_PRIMITIVE_TYPES: &[&str] = &[
+    "bool", "char", "str", "f32", "f64", "i8", "i16", "i32", "i64", "i128", "isize",
+    "u8", "u16", "u32", "u64", "u128", "usize",
+];
+
+fn classify_rust(source: &str, name: &str, prev: Token, next: Token, in_fields: bool) -> Option<IdentifierKind> {
+    let prev_word = match prev {
+        Token::Identifier(start, end) => &source[start..end],
+        Token::Punctuation(p) => p,
+        _ => "",
+    };
+
+    if RUST_PRIMITIVE_TYPES.contains(&name) {
+        return Some(IdentifierKind::Type);
+    }
+
+    match (prev_word, next) {
+        (_, Token::Punctuation("!")) => None,
+        ("mod" | "use" | "crate" | "extern" | "'", _) => None,
+        ("fn", _) => Some(IdentifierKind::Function),
+        ("struct" | "enum" | "trait" | "type" | "union", _) => Some(IdentifierKind::Type),
+        ("const" | "static", _) => Some(IdentifierKind::Constant),
+        ("let" | "mut" | "for", _) => Some(IdentifierKind::Variable),
+        (".", Token::Punctuation("(" | "::")) => Some(IdentifierKind::Function),
+        (".", _) => Some(IdentifierKind::Field),
+        ("{" | "," | "pub" | ")", Token::Punctuation(":")) if in_fields => Some(IdentifierKind::Field),
+        (_, Token::Punctuation("::")) if !starts_uppercase(name) => None,
+        (_, Token::Punctuation("(")) if !starts_uppercase(name) => Some(IdentifierKind::Function),
+        _ => Some(classify_by_shape(name)),
+    }
+}
+
+fn classify_python(source: &str, name: &str, prev: Token, next: Token) -> Option<IdentifierKind> {
+    let prev_word = match prev {
+        Token::Identifier(start, end) => &source[start..end],
+        Token::Punctuation(p) => p,
+        _ => "",
+    };
+
+    // The receivers of methods are named by convention rather than by the style of the code
+    if name == "self" || name == "cls" {
+        return None;
+    }
+
+    match (prev_word, next) {
+        ("def", _) => Some(IdentifierKind::Function),
+        ("class", _) => Some(IdentifierKind::Type),
+        (".", Token::Punctuation("(")) => Some(IdentifierKind::Function),
+        (".", _) => Some(IdentifierKind::Field),
+        (_, Token::Punctuation("(")) if !starts_uppercase(name) => Some(IdentifierKind::Function),
+        _ => Some(classify_by_shape(name)),
+    }
+}
+
+/// Keywords after which a table name comes
+const SQL_TABLE_KEYWORDS: &[&str] = &["from", "join", "into", "update", "table", "view", "references", "exists"];
+
+/// `prev_kind` is the kind of the previous token if it's an identifier too, or if the
+/// previous token is AS, of the identifier before it
+fn classify_sql(source: &str, prev: Token, prev_kind: Option<IdentifierKind>, next: Token) -> Option<IdentifierKind> {
+    let prev_word = match prev {
+        Token::Identifier(start, end) => source[start..end].to_lowercase(),
+        Token::Punctuation(p) => String::from(p),
+        _ => String::new(),
+    };
+
+    match (prev_word.as_str(), next) {
+        (word, _) if SQL_TABLE_KEYWORDS.contains(&word) => Some(IdentifierKind::Type),
+        // The alias of a table, given with or without AS
+        _ if prev_kind == Some(IdentifierKind::Type) => Some(IdentifierKind::Type),
+        // The alias of a column or expression, which isn't a column itself
+        ("as", _) => Some(IdentifierKind::Alias),
+        // A table or alias qualifying a column
+        (_, Token::Punctuation(".")) => Some(IdentifierKind::Type),
+        (".", _) => Some(IdentifierKind::Field),
+        (_, Token::Punctuation("(")) => Some(IdentifierKind::Function),
+        _ => Some(IdentifierKind::Field),
+    }
+}
+
+fn classify_by_shape(name: &str) -> IdentifierKind {
+    let has_lowercase = name.chars().any(|c| c.is_lowercase());
+    let has_uppercase = name.chars().any(|c| c.is_uppercase());
+
+    if has_uppercase && !has_lowercase && name.chars().count() > 1 {
+        IdentifierKind::Constant
+    } else if starts_uppercase(name) {
+        IdentifierKind::Type
+    } else {
+        IdentifierKind::Variable
+    }
+}
+
+fn starts_uppercase(text: &str) -> bool {
+    text.chars().next().map(|c| c.is_uppercase()).unwrap_or(false)
+}
+
+fn is_identifier_start(byte: u8) -> bool {
+    byte.is_ascii_alphabetic() || byte == b'_' || byte >= 0x80
+}
+
+fn is_identifier_continue(byte: u8) -> bool {
+    is_identifier_start(byte) || byte.is_ascii_digit()
+}
+
+fn tokenize(source: &str, language: Language) -> Vec<Token<'_>> {
+    let bytes = source.as_bytes();
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        let byte = bytes[pos];
+        let rest = &bytes[pos..];
+
+        if byte == b'\n' {
+            if language == Language::Python {
+                tokens.push(Token::Newline);
+            }
+            pos += 1;
+        } else if byte.is_ascii_whitespace() {
+            pos += 1;
+        } else if language == Language::Rust && rest.starts_with(b"//")
+               || language == Language::Python && byte == b'#'
+               || language == Language::Sql && rest.starts_with(b"--") {
+            pos = skip_line(bytes, pos);
+        } else if language != Language::Python && rest.starts_with(b"/*") {
+            pos = skip_block_comment(bytes, pos);
+        } else if language == Language::Sql && (byte == b'"' || byte == b'`') {
+            // An unterminated identifier runs to the end of the source
+            let end = find_doubled(bytes, pos + 1, byte).unwrap_or(bytes.len());
+            // Identifiers with escaped quotes aren't names as they're written, so they're left as they are
+            if bytes[pos + 1..end].windows(2).any(|pair| pair == [byte, byte]) {
+                tokens.push(Token::Literal);
+            } else {
+                tokens.push(Token::QuotedIdentifier(pos + 1, end));
+            }
+            pos = (end + 1).min(bytes.len());
+        } else if language == Language::Rust && rest.starts_with(b"r#") && rest.get(2).copied().is_some_and(is_identifier_start) {
+            let start = pos + 2;
+            pos = start;
+            while pos < bytes.len() && is_identifier_continue(bytes[pos]) {
+                pos += 1;
+            }
+            tokens.push(Token::QuotedIdentifier(start, pos));
+        } else if let Some(end) = string_literal(bytes, pos, language) {
+            tokens.push(Token::Literal);
+            pos = end;
+        } else if language == Language::Rust && byte == b'\'' {
+            if let Some(end) = char_literal(source, pos) {
+                tokens.push(Token::Literal);
+                pos = end;
+            } else {
+                // A lifetime or a label
+                tokens.push(Token::Punctuation("'"));
+                pos += 1;
+            }
+        } else if byte.is_ascii_digit() {
+            while pos < bytes.len() && (is_identifier_continue(bytes[pos]) || bytes[pos] == b'.' && bytes.get(pos + 1).is_some_and(u8::is_ascii_digit)) {
+                pos += 1;
+            }
+            tokens.push(Token::Literal);
+        } else if is_identifier_start(byte) {
+            let start = pos;
+            while pos < bytes.len() && is_identifier_continue(bytes[pos]) {
+                pos += 1;
+            }
+            tokens.push(Token::Identifier(start, pos));
+        } else {
+            let len = match rest {
+                [b':', b':', ..] | [b'!', b'=', ..] | [b'-', b'>', ..] | [b'=', b'>', ..] | [b'=', b'=', ..] => 2,
+                _ => source[pos..].chars().next().map(char::len_utf8).unwrap_or(1),
+            };
+            tokens.push(Token::Punctuation(&source[pos..pos + len]));
+            pos += len;
+        }
+    }
+
+    tokens
+}
+
+fn skip_line(bytes: &[u8], pos: usize) -> usize {
+    bytes[pos..].iter()
+                .position(|b| *b == b'\n')
+                .map(|offset| pos + offset)
+                .unwrap_or(bytes.len())
+}
+
+fn skip_block_comment(bytes: &[u8], mut pos: usize) -> usize {
+    let mut depth = 0;
+    while pos < bytes.len() {
+        if bytes[pos..].starts_with(b"/*") {
+            depth += 1;
+            pos += 2;
+        } else if bytes[pos..].starts_with(b"*/") {
+            depth -= 1;
+            pos += 2;
+            if depth == 0 {
+                break;
+            }
+        } else {
+            pos += 1;
+        }
+    }
+    pos
+}
+
+/// If a string literal (including its prefix, if any) starts at `pos`,
+/// returns the position right after it
+fn string_literal(bytes: &[u8], pos: usize, language: Language) -> Option<usize> {
+    let prefix_len = bytes[pos..].iter().take_while(|b| b.is_ascii_alphabetic()).count();
+    let prefix = std::str::from_utf8(&bytes[pos..pos + prefix_len]).ok()?;
+
+    match language {
+        Language::Rust => {
+            if !matches!(prefix, "" | "b" | "c" | "r" | "br" | "cr") {
+                return None;
+            }
+            let mut quote_pos = pos + prefix_len;
+            if prefix.ends_with('r') {
+                let hashes = bytes[quote_pos..].iter().take_while(|b| **b == b'#').count();
+                quote_pos += hashes;
+                if bytes.get(quote_pos) != Some(&b'"') {
+                    return None;
+                }
+                let mut closing = vec![b'"'];
+                closing.extend(std::iter::repeat_n(b'#', hashes));
+                return Some(find_after(bytes, quote_pos + 1, &closing));
+            }
+            if bytes.get(quote_pos) != Some(&b'"') {
+                return None;
+            }
+            quote_pos += 1;
+            Some(skip_escaped(bytes, quote_pos, b"\""))
+        },
+        Language::Sql => {
+            if !matches!(prefix.to_lowercase().as_str(), "" | "n" | "e" | "b" | "x") || bytes.get(pos + prefix_len) != Some(&b'\'') {
+                return None;
+            }
+            Some(skip_doubled(bytes, pos + prefix_len + 1, b'\''))
+        },
+        Language::Python => {
+            let prefix = prefix.to_lowercase();
+            let valid_prefix = prefix.len() <= 2 && prefix.chars().all(|c| matches!(c, 'r' | 'b' | 'u' | 'f'));
+            if !valid_prefix {
+                return None;
+            }
+            let quote_pos = pos + prefix_len;
+            let quote = *bytes.get(quote_pos)?;
+            if quote != b'"' && quote != b'\'' {
+                return None;
+            }
+            let raw = prefix.contains('r');
+            let triple = [quote; 3];
+            if bytes[quote_pos..].starts_with(&triple) {
+                if raw {
+                    Some(find_after(bytes, quote_pos + 3, &triple))
+                } else {
+                    Some(skip_escaped(bytes, quote_pos + 3, &triple))
+                }
+            } else if raw {
+                Some(find_after(bytes, quote_pos + 1, &[quote]))
+            } else {
+                Some(skip_escaped(bytes, quote_pos + 1, &[quote]))
+            }
+        },
+    }
+}
+
+/// If a character literal starts at `pos`, returns the position right after it
+fn char_literal(source: &str, pos: usize) -> Option<usize> {
+    let mut chars = source[pos + 1..].chars();
+    match chars.next()? {
+        '\\' => Some(skip_escaped(source.as_bytes(), pos + 1, b"'")),
+        c => (chars.next() == Some('\'')).then_some(pos + 2 + c.len_utf8()),
+    }
+}
+
+fn find_after(bytes: &[u8], from: usize, closing: &[u8]) -> usize {
+    (from..bytes.len())
+        .find(|i| bytes[*i..].starts_with(closing))
+        .map(|i| i + closing.len())
+        .unwrap_or(bytes.len())
+}
+
+/// Finds the closing quote, where two quotes in a row are an escaped one
+fn find_doubled(bytes: &[u8], mut pos: usize, quote: u8) -> Option<usize> {
+    while pos < bytes.len() {
+        if bytes[pos] == quote && bytes.get(pos + 1) == Some(&quote) {
+            pos += 2;
+        } else if bytes[pos] == quote {
+            return Some(pos);
+        } else {
+            pos += 1;
+        }
+    }
+    None
+}
+
+/// Skips to right after the closing quote, where two quotes in a row are an escaped one
+fn skip_doubled(bytes: &[u8], pos: usize, quote: u8) -> usize {
+    find_doubled(bytes, pos, quote).map(|end| end + 1).unwrap_or(bytes.len())
+}
+
+fn skip_escaped(bytes: &[u8], mut pos: usize, closing: &[u8]) -> usize {
+    while pos < bytes.len() {
+        if bytes[pos] == b'\\' {
+            pos += 2;
+        } else if bytes[pos..].starts_with(closing) {
+            return pos + closing.len();
+        } else {
+            pos += 1;
+        }
+    }
+    bytes.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    fn names_of_kind(source: &str, language: Language, kind: IdentifierKind) -> Vec<&str> {
+        identifiers(source, language).into_iter()
+                                     .filter(|it| it.kind == kind)
+                                     .map(|it| it.name)
+                                     .collect()
+    }
+
+    #[test]
+    fn language_from_path() {
+        assert_eq!(Language::from_path("src/main.rs"), Some(Language::Rust));
+        assert_eq!(Language::from_path("script.py"), Some(Language::Python));
+        assert_eq!(Language::from_path("schema.sql"), Some(Language::Sql));
+        assert_eq!(Language::from_path("README.md"), None);
+    }
+
+    #[test]
+    fn classifies_rust_identifiers() {
+        // ARRANGE
+        let source = indoc! {r#"
+            const MAX_RETRIES: u32 = 3;
+
+            struct UserAccount {
+                userId: u32,
+                pub displayName: String,
+            }
+
+            fn getUser(accountId: u32) -> UserAccount {
+                let mut retryCount = 0;
+                println!("getUser {}", accountId);
+                UserAccount { userId: accountId, displayName: loadName(accountId) }
+            }
+        "#};
+
+        // ACT & ASSERT
+        assert_eq!(names_of_kind(source, Language::Rust, IdentifierKind::Type), vec!["u32", "UserAccount", "u32", "String", "u32", "UserAccount", "UserAccount"]);
+        assert_eq!(names_of_kind(source, Language::Rust, IdentifierKind::Function), vec!["getUser", "loadName"]);
+        assert_eq!(names_of_kind(source, Language::Rust, IdentifierKind::Variable), vec!["accountId", "retryCount", "accountId", "accountId", "accountId"]);
+        assert_eq!(names_of_kind(source, Language::Rust, IdentifierKind::Constant), vec!["MAX_RETRIES"]);
+        assert_eq!(names_of_kind(source, Language::Rust, IdentifierKind::Field), vec!["userId", "displayName", "userId", "displayName"]);
+    }
+
+    #[test]
+    fn skips_rust_comments_and_literals() {
+        // ARRANGE
+        let source = indoc! {r##"
+            // someComment
+            /* other /* nested */ comment */
+            fn f<'a>(x: &'a str) -> char {
+                let s = r#"rawString "quoted""#;
+                'c'
+            }
+        "##};
+
+        // ACT
+        let names: Vec<&str> = identifiers(source, Language::Rust).into_iter().map(|it| it.name).collect();
+
+        // ASSERT
+        assert_eq!(names, vec!["f", "x", "str", "char", "s"]);
+    }
+
+    #[test]
+    fn finds_rust_raw_identifiers() {
+        let source = r##"fn r#match(r#type: u8) { let s = r#"r#raw"#; }"##;
+
+        let result = identifiers(source, Language::Rust);
+
+        assert_eq!(result.iter().map(|it| (it.name, it.kind)).collect::<Vec<_>>(), vec![
+            ("match", IdentifierKind::Function),
+            ("type", IdentifierKind::Variable),
+            ("u8", IdentifierKind::Type),
+            ("s", IdentifierKind::Variable),
+        ]);
+        assert_eq!(&source[result[1].span.clone()], "type");
+    }
+
+    #[test]
+    fn classifies_python_identifiers() {
+        // ARRANGE
+        let source = indoc! {r#"
+            import os.path
+            from typing import List
+            from .accounts import fetch_name
+
+            MAX_SIZE = 10
+
+            class UserAccount:
+                def getName(self, userId):
+                    """Returns someName"""
+                    self.lastName = fetch_name(userId)  # cachedName
+                    return self.lastName.strip()
+        "#};
+
+        // ACT & ASSERT
+        assert_eq!(names_of_kind(source, Language::Python, IdentifierKind::Type), vec!["List", "UserAccount"]);
+        assert_eq!(names_of_kind(source, Language::Python, IdentifierKind::Function), vec!["getName", "fetch_name", "strip"]);
+        assert_eq!(names_of_kind(source, Language::Python, IdentifierKind::Variable), vec!["fetch_name", "userId", "userId"]);
+        assert_eq!(names_of_kind(source, Language::Python, IdentifierKind::Constant), vec!["MAX_SIZE"]);
+        assert_eq!(names_of_kind(source, Language::Python, IdentifierKind::Field), vec!["lastName", "lastName"]);
+    }
+
+    #[test]
+    fn guesses_kinds_from_neighbouring_tokens() {
+        // ARRANGE
+        let rust = indoc! {"
+            let makeUser = |userId| userId;
+            makeUser(1);
+        "};
+        let python = indoc! {"
+            def load(self, cls):
+                UserClass = make_class()
+                handler = self.onEvent
+                handler()
+        "};
+
+        // ACT & ASSERT
+        // Calling a variable makes it look like a function
+        assert_eq!(names_of_kind(rust, Language::Rust, IdentifierKind::Function), vec!["makeUser"]);
+        assert_eq!(names_of_kind(python, Language::Python, IdentifierKind::Function), vec!["load", "make_class", "handler"]);
+        // And a PascalCase variable like a type
+        assert_eq!(names_of_kind(python, Language::Python, IdentifierKind::Type), vec!["UserClass"]);
+        assert_eq!(names_of_kind(python, Language::Python, IdentifierKind::Variable), vec!["handler"]);
+    }
+
+    #[test]
+    fn classifies_sql_identifiers() {
+        // ARRANGE
+        let source = indoc! {r#"
+            -- userAccounts keeps someComment
+            CREATE TABLE IF NOT EXISTS "userAccounts" (
+                userId BIGINT PRIMARY KEY,
+                `displayName` VARCHAR(100) DEFAULT 'someName' /* lastName */
+            );
+
+            select ua.userId, Count(*) as loginCount, normalizeName(displayName) AS "shownName"
+            from userAccounts ua join loginEvents as le on le.accountId = ua.userId
+            where ua.displayName <> 'it''s someValue';
+        "#};
+
+        // ACT & ASSERT
+        assert_eq!(names_of_kind(source, Language::Sql, IdentifierKind::Type), vec!["userAccounts", "ua", "userAccounts", "ua", "loginEvents", "le", "le", "ua", "ua"]);
+        assert_eq!(names_of_kind(source, Language::Sql, IdentifierKind::Function), vec!["normalizeName"]);
+        assert_eq!(names_of_kind(source, Language::Sql, IdentifierKind::Field), vec![
+            "userId", "displayName", "userId", "displayName", "accountId", "userId", "displayName",
+        ]);
+        assert_eq!(names_of_kind(source, Language::Sql, IdentifierKind::Alias), vec!["loginCount", "shownName"]);
+    }
+
+    #[test]
+    fn sql_quoted_identifier_spans() {
+        let source = r#"SELECT "firstName" FROM `userAccounts`"#;
+
+        let result = identifiers(source, Language::Sql);
+
+        assert_eq!(result.iter().map(|it| it.span.clone()).collect::<Vec<_>>(), vec![8..17, 25..37]);
+        assert_eq!(result.iter().map(|it| it.name).collect::<Vec<_>>(), vec!["firstName", "userAccounts"]);
+    }
+
+    #[test]
+    fn skips_sql_identifiers_with_escaped_quotes() {
+        let source = r#"SELECT "fooBar""Baz", `user``Id`, "userName" FROM t"#;
+
+        let names: Vec<&str> = identifiers(source, Language::Sql).into_iter().map(|it| it.name).collect();
+
+        assert_eq!(names, vec!["userName", "t"]);
+    }
+
+    #[test]
+    fn sql_unterminated_quoted_identifier() {
+        let source = r#"SELECT "é"#;
+
+        let result = identifiers(source, Language::Sql);
+
+        assert_eq!(result.iter().map(|it| it.name).collect::<Vec<_>>(), vec!["é"]);
+        assert_eq!(result[0].span, 8..10);
+    }
+
+    #[test]
+    fn identifier_spans_point_to_source() {
+        let source = "let fooBar = 1;";
+
+        let result = identifiers(source, Language::Rust);
+
+        assert_eq!(result, vec![Identifier { name: "fooBar", span: 4..10, kind: IdentifierKind::Variable }]);
+        assert_eq!(&source[result[0].span.clone()], "fooBar");
+    }
+}
@@ -0,0 +1,239 @@
+use clap::ValueEnum;
+use std::ops::Range;
+use std::path::Path;
+use thiserror::Error;
+use crate::scan;
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, ValueEnum)]
+pub enum TableFormat {
+    Csv,
+    Tsv,
+}
+
+impl TableFormat {
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<TableFormat> {
+        match path.as_ref().extension()?.to_str()? {
+            "csv" => Some(TableFormat::Csv),
+            "tsv" | "tab" => Some(TableFormat::Tsv),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum TableError {
+    #[error("unterminated quoted cell starting at line {0}, column {1}")]
+    UnterminatedQuoteError(usize, usize),
+    #[error("there's no column '{0}' in the header")]
+    UnknownColumnError(String),
+}
+
+/// How the cells of a table are separated and quoted
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Dialect {
+    pub delimiter: u8,
+    /// Cells starting with this character extend until the next unescaped one,
+    /// which is escaped by doubling it
+    pub quote: Option<u8>,
+}
+
+impl From<TableFormat> for Dialect {
+    fn from(format: TableFormat) -> Self {
+        match format {
+            TableFormat::Csv => Dialect { delimiter: b',', quote: Some(b'"') },
+            TableFormat::Tsv => Dialect { delimiter: b'\t', quote: None },
+        }
+    }
+}
+
+/// A cell of a table. `span` is the byte span of its text in the table, excluding quotes
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Cell<'a> {
+    pub text: &'a str,
+    pub span: Range<usize>,
+}
+
+/// Splits `source` into rows of cells
+pub fn rows<'a>(source: &'a str, dialect: &Dialect) -> Result<Vec<Vec<Cell<'a>>>, TableError> {
+    let bytes = source.as_bytes();
+    let mut pos = if source.starts_with('\u{feff}') { '\u{feff}'.len_utf8() } else { 0 };
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+
+    while pos < bytes.len() {
+        let span = if dialect.quote.is_some() && dialect.quote == bytes.get(pos).copied() {
+            let quote = bytes[pos];
+            let start = pos + 1;
+            pos = start;
+            loop {
+                match bytes.get(pos) {
+                    None => {
+                        let (line, column) = scan::line_column(source, start - 1);
+                        return Err(TableError::UnterminatedQuoteError(line, column));
+                    },
+                    Some(byte) if *byte == quote && bytes.get(pos + 1) == Some(&quote) => pos += 2,
+                    Some(byte) if *byte == quote => break,
+                    Some(_) => pos += 1,
+                }
+            }
+            let end = pos;
+            // Anything between the closing quote and the end of the cell is ignored
+            while pos < bytes.len() && bytes[pos] != dialect.delimiter && bytes[pos] != b'\n' {
+                pos += 1;
+            }
+            start..end
+        } else {
+            let start = pos;
+            while pos < bytes.len() && bytes[pos] != dialect.delimiter && bytes[pos] != b'\n' {
+                pos += 1;
+            }
+            let end = if pos > start && bytes[pos - 1] == b'\r' { pos - 1 } else { pos };
+            start..end
+        };
+        row.push(Cell { text: &source[span.clone()], span });
+
+        match bytes.get(pos) {
+            Some(byte) if *byte == dialect.delimiter => pos += 1,
+            _ => {
+                pos += 1;
+                rows.push(std::mem::take(&mut row));
+            },
+        }
+    }
+
+    // A last row ending in a delimiter without a newline still has an empty cell after it
+    if !row.is_empty() {
+        row.push(Cell { text: "", span: source.len()..source.len() });
+        rows.push(row);
+    }
+
+    Ok(rows)
+}
+
+/// The cells of the header row of `source`, or if some columns are given, the cells
+/// of those columns in all the other rows. Columns are given by their name in the
+/// header or, if there's no such name, by their 1-based number. Cells with escaped
+/// quotes aren't names as they're written, so they're left out
+pub fn selected_cells<'a>(source: &'a str, dialect: &Dialect, columns: &[String]) -> Result<Vec<Cell<'a>>, TableError> {
+    let escaped = |cell: &Cell| dialect.quote.is_some_and(|quote| cell.text.as_bytes().windows(2).any(|pair| pair == [quote, quote]));
+
+    let mut rows = rows(source, dialect)?.into_iter();
+    let Some(header) = rows.next() else { return Ok(Vec::new()) };
+    if columns.is_empty() {
+        return Ok(header.into_iter().filter(|cell| !escaped(cell)).collect());
+    }
+
+    let indices = columns.iter()
+                         .map(|column| {
+                             header.iter()
+                                   .position(|cell| cell.text == column)
+                                   .or_else(|| column.parse::<usize>().ok().filter(|number| *number > 0).map(|number| number - 1))
+                                   .ok_or_else(|| TableError::UnknownColumnError(column.clone()))
+                         })
+                         .collect::<Result<Vec<usize>, TableError>>()?;
+
+    Ok(rows.flat_map(|row| row.into_iter().enumerate())
+           .filter(|(index, _)| indices.contains(index))
+           .map(|(_, cell)| cell)
+           .filter(|cell| !escaped(cell))
+           .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    fn texts<'a>(cells: &[Cell<'a>]) -> Vec<&'a str> {
+        cells.iter().map(|cell| cell.text).collect()
+    }
+
+    #[test]
+    fn splits_quoted_cells() -> Result<(), TableError> {
+        // ARRANGE
+        let source = "userId,\"firstName, lastName\",\"say \"\"hi\"\"\"\r\n1,,x\n";
+
+        // ACT
+        let rows = rows(source, &Dialect::from(TableFormat::Csv))?;
+
+        // ASSERT
+        assert_eq!(rows.len(), 2);
+        assert_eq!(texts(&rows[0]), vec!["userId", "firstName, lastName", "say \"\"hi\"\""]);
+        assert_eq!(texts(&rows[1]), vec!["1", "", "x"]);
+        assert_eq!(rows[0][1].span, 8..27);
+        Ok(())
+    }
+
+    #[test]
+    fn tsv_cells_are_not_quoted() -> Result<(), TableError> {
+        let rows = rows("\"userId\tuserName\n", &Dialect::from(TableFormat::Tsv))?;
+
+        assert_eq!(texts(&rows[0]), vec!["\"userId", "userName"]);
+        Ok(())
+    }
+
+    #[test]
+    fn keeps_last_row_ending_in_delimiter() -> Result<(), TableError> {
+        // ARRANGE
+        let source = indoc! {"
+            userId,userName
+            a,b,"
+        };
+
+        // ACT
+        let rows = rows(source, &Dialect::from(TableFormat::Csv))?;
+
+        // ASSERT
+        assert_eq!(rows.len(), 2);
+        assert_eq!(texts(&rows[1]), vec!["a", "b", ""]);
+        assert_eq!(rows[1][2].span, 20..20);
+        Ok(())
+    }
+
+    #[test]
+    fn unterminated_quote() {
+        let result = rows("a,b\n1,\"2\n", &Dialect::from(TableFormat::Csv));
+
+        assert_eq!(result, Err(TableError::UnterminatedQuoteError(2, 3)));
+    }
+
+    #[test]
+    fn selects_header_or_columns() -> Result<(), TableError> {
+        // ARRANGE
+        let source = indoc! {"
+            userId;userRole;lastLogin
+            1;adminUser;today
+            2;guestUser;never
+        "};
+        let dialect = Dialect { delimiter: b';', quote: Some(b'"') };
+
+        // ACT
+        let header = selected_cells(source, &dialect, &[])?;
+        let columns = selected_cells(source, &dialect, &[String::from("userRole"), String::from("3")])?;
+
+        // ASSERT
+        assert_eq!(texts(&header), vec!["userId", "userRole", "lastLogin"]);
+        assert_eq!(texts(&columns), vec!["adminUser", "today", "guestUser", "never"]);
+        assert_eq!(selected_cells(source, &dialect, &[String::from("other")]), Err(TableError::UnknownColumnError(String::from("other"))));
+        Ok(())
+    }
+
+    #[test]
+    fn leaves_out_cells_with_escaped_quotes() -> Result<(), TableError> {
+        // ARRANGE
+        let source = indoc! {r#"
+            userId,"say ""hi""",userName
+            1,"a""b",guestUser
+        "#};
+        let dialect = Dialect::from(TableFormat::Csv);
+
+        // ACT
+        let header = selected_cells(source, &dialect, &[])?;
+        let columns = selected_cells(source, &dialect, &[String::from("2"), String::from("userName")])?;
+
+        // ASSERT
+        assert_eq!(texts(&header), vec!["userId", "userName"]);
+        assert_eq!(texts(&columns), vec!["guestUser"]);
+        Ok(())
+    }
+}
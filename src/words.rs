@@ -0,0 +1,137 @@
+use crate::case::Case;
+use std::ops::Range;
+
+/// A word of a token, with its original casing and its byte span in the token
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Word {
+    pub text: String,
+    pub span: Range<usize>,
+}
+
+/// Splits a token into words. Any character that is not a letter or digit (in
+/// any script) separates words, and so do changes from lowercase to uppercase (`fooBar`) and
+/// the last uppercase letter before a lowercase one (`XMLHttp`)
+pub fn split_words(token: &str) -> Vec<Word> {
+    let mut words = Vec::new();
+    let mut push_word = |range: Range<usize>| {
+        words.push(Word { text: String::from(&token[range.clone()]), span: range });
+    };
+
+    for chunk in token.split(|c: char| !c.is_alphanumeric()) {
+        let offset = chunk.as_ptr() as usize - token.as_ptr() as usize;
+
+        let mut chars = chunk.char_indices().peekable();
+        let mut word_start = 0;
+        // Whether the last cased character in the current word is uppercase
+        let mut last_uppercase: Option<bool> = None;
+
+        while let Some((i, c)) = chars.next() {
+            let Some(&(next_i, next)) = chars.peek() else {
+                push_word(offset + word_start..offset + chunk.len());
+                break;
+            };
+
+            let next_last_uppercase = if c.is_lowercase() {
+                Some(false)
+            } else if c.is_uppercase() {
+                Some(true)
+            } else {
+                last_uppercase
+            };
+
+            if next_last_uppercase == Some(false) && next.is_uppercase() {
+                push_word(offset + word_start..offset + next_i);
+                word_start = next_i;
+                last_uppercase = None;
+            } else if last_uppercase == Some(true) && c.is_uppercase() && next.is_lowercase() {
+                push_word(offset + word_start..offset + i);
+                word_start = i;
+                last_uppercase = None;
+            } else {
+                last_uppercase = next_last_uppercase;
+            }
+        }
+    }
+
+    words
+}
+
+/// Renders words in the given case
+pub fn join_words(words: &[Word], case: Case) -> String {
+    let words = words.iter().map(|word| word.text.as_str());
+
+    match case {
+        Case::PascalCase => words.map(capitalize).collect(),
+        Case::CamelCase => words.enumerate()
+                                .map(|(i, word)| if i == 0 { word.to_lowercase() } else { capitalize(word) })
+                                .collect(),
+        Case::SnakeCase => words.map(str::to_lowercase).collect::<Vec<_>>().join("_"),
+        Case::ShoutingSnakeCase => words.map(str::to_uppercase).collect::<Vec<_>>().join("_"),
+        Case::KebabCase => words.map(str::to_lowercase).collect::<Vec<_>>().join("-"),
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars.as_str().to_lowercase().chars()).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(words: &[Word]) -> Vec<&str> {
+        words.iter().map(|word| word.text.as_str()).collect()
+    }
+
+    #[test]
+    fn splits_words_with_spans() {
+        let words = split_words("XMLHttpRequest");
+
+        assert_eq!(words, vec![
+            Word { text: String::from("XML"), span: 0..3 },
+            Word { text: String::from("Http"), span: 3..7 },
+            Word { text: String::from("Request"), span: 7..14 },
+        ]);
+    }
+
+    #[test]
+    fn splits_on_separators() {
+        assert_eq!(texts(&split_words("this-contains_ ALLKinds OfWord_Boundaries")), vec![
+            "this", "contains", "ALL", "Kinds", "Of", "Word", "Boundaries",
+        ]);
+        assert_eq!(split_words("__foo")[0].span, 2..5);
+    }
+
+    #[test]
+    fn keeps_digits_in_words() {
+        assert_eq!(texts(&split_words("version2Update")), vec!["version2", "Update"]);
+    }
+
+    #[test]
+    fn keeps_non_ascii_letters() {
+        let words = split_words("größeZahl");
+
+        assert_eq!(words, vec![
+            Word { text: String::from("größe"), span: 0..7 },
+            Word { text: String::from("Zahl"), span: 7..11 },
+        ]);
+        assert_eq!(join_words(&split_words(&join_words(&words, Case::SnakeCase)), Case::CamelCase), "größeZahl");
+        assert_eq!(join_words(&split_words("café_name"), Case::PascalCase), "CaféName");
+        assert_eq!(join_words(&split_words("ÉTAT_CIVIL"), Case::KebabCase), "état-civil");
+    }
+
+    #[test]
+    fn joins_words_in_every_case() {
+        let words = split_words("XMLHttpRequest");
+
+        assert_eq!(join_words(&words, Case::PascalCase), "XmlHttpRequest");
+        assert_eq!(join_words(&words, Case::CamelCase), "xmlHttpRequest");
+        assert_eq!(join_words(&words, Case::SnakeCase), "xml_http_request");
+        assert_eq!(join_words(&words, Case::ShoutingSnakeCase), "XML_HTTP_REQUEST");
+        assert_eq!(join_words(&words, Case::KebabCase), "xml-http-request");
+    }
+}